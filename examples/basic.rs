@@ -3,7 +3,7 @@ use std::f32::consts::PI;
 use bevy::prelude::*;
 use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_rts_cursor::{
-    Bounds2D, CursorPlugin, CursorReflector, Pickable, RayReflector, RaycastSource,
+    Bounds2D, CursorPlugin, CursorReflector, Pickable, RayReflector, RaycastSource, WorldCursor,
 };
 
 pub const GAME_X_MIN: f32 = 0.0;
@@ -32,7 +32,6 @@ fn main() {
         })
         .add_startup_system(setup)
         .add_system(camera_controls)
-        .add_system(bevy::window::close_on_esc)
         .run();
 }
 
@@ -80,6 +79,22 @@ fn setup(
         })
         .insert(CursorReflector);
 
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Torus {
+                radius: 0.3,
+                ring_radius: 0.05,
+                ..default()
+            })),
+            material: materials.add(StandardMaterial {
+                emissive: Color::WHITE,
+                ..default()
+            }),
+            ..Default::default()
+        })
+        .insert(WorldCursor::default())
+        .insert(Name::new("WorldCursor"));
+
     let box_size = 2.;
     let half_size = box_size / 2.;
     commands
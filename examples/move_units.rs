@@ -1,11 +1,9 @@
-// TODO: Add a system to add a Destination for units.
-
 use std::f32::consts::PI;
 
 use bevy::prelude::*;
 use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_rts_cursor::{
-    Bounds2D, Cursor, CursorPlugin, CursorReflector, Pickable, RayReflector, RaycastSource,
+    Bounds2D, CursorPlugin, CursorReflector, Pickable, RayReflector, RaycastSource, WaypointQueue,
 };
 
 pub const GAME_X_MIN: f32 = -16.0;
@@ -38,10 +36,6 @@ pub struct MovementSpeed {
     pub value: f32,
 }
 
-#[derive(Component, Reflect, Default, Debug)]
-#[reflect(Component)]
-pub struct Destination(pub Vec3);
-
 #[derive(Resource, Default)]
 pub struct Game {
     mechanics: Mechanics,
@@ -64,10 +58,8 @@ fn main() {
         })
         .add_startup_system(setup)
         .add_system(camera_controls)
-        .add_system(bevy::window::close_on_esc)
         .add_system(movement_system)
         .add_system(adjust_still_units_system)
-        .add_system(select_destination)
         .run();
 }
 
@@ -195,7 +187,7 @@ fn camera_controls(
 pub fn adjust_still_units_system(
     mut units: Query<
         (Entity, &mut Transform, &MovementSpeed),
-        (With<MovementSpeed>, Without<Destination>),
+        (With<MovementSpeed>, Without<WaypointQueue>),
     >,
     mut game: ResMut<Game>,
     time: Res<Time>,
@@ -226,38 +218,28 @@ pub fn adjust_still_units_system(
     }
 }
 
-pub fn select_destination(
-    mut commands: Commands,
-    cursor: Res<Cursor>,
-    buttons: Res<Input<MouseButton>>,
-) {
-    if buttons.just_pressed(MouseButton::Left) {
-        for unit in &cursor.selection.selected_units {
-            println!("Adding destination to {:?}", unit);
-            commands.entity(*unit).insert(Destination(cursor.location));
-        }
-    }
-}
-
 pub fn movement_system(
     mut commands: Commands,
     time: Res<Time>,
     mut game: ResMut<Game>,
     mut units: Query<
-        (Entity, &mut Transform, &mut Destination, &MovementSpeed),
-        (With<Destination>, With<MovementSpeed>),
+        (Entity, &mut Transform, &mut WaypointQueue, &MovementSpeed),
+        (With<WaypointQueue>, With<MovementSpeed>),
     >,
 ) {
     let units_positions: Vec<(Entity, Vec3)> = units
         .into_iter()
         .map(|t| return (t.0, t.1.translation))
         .collect();
-    for (entity, mut transform, destination, speed) in &mut units {
+    for (entity, mut transform, mut waypoints, speed) in &mut units {
+        let Some(destination) = waypoints.current() else {
+            continue;
+        };
         if game.mechanics.move_cooldown.tick(time.delta()).finished() {
             let new_destination = adjust_movement_for_neighbors(
                 &entity,
                 &transform.translation,
-                destination.0,
+                destination,
                 &units_positions,
             );
             transform.translation = move_unit(
@@ -269,8 +251,9 @@ pub fn movement_system(
             stop_at_destination(
                 &mut commands,
                 entity,
+                &mut waypoints,
                 transform.translation,
-                destination.0,
+                destination,
                 ARRIVAL_TOLERANCE,
             );
         }
@@ -280,12 +263,16 @@ pub fn movement_system(
 fn stop_at_destination(
     commands: &mut Commands,
     unit: Entity,
+    waypoints: &mut Mut<WaypointQueue>,
     unit_position: Vec3,
     destination: Vec3,
     arrival_tolerance: f32,
 ) -> () {
     if are_positions_near(&destination, &unit_position, arrival_tolerance) {
-        commands.entity(unit).remove::<Destination>();
+        waypoints.advance();
+        if waypoints.is_empty() {
+            commands.entity(unit).remove::<WaypointQueue>();
+        }
     }
 }
 
@@ -59,7 +59,6 @@ fn main() {
         })
         .init_resource::<Game>()
         .add_system(move_scene_entities)
-        .add_system(bevy::window::close_on_esc)
         .add_state(GameState::Playing)
         .add_startup_system(setup)
         // .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(setup))
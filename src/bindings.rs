@@ -0,0 +1,294 @@
+use std::{collections::HashSet, fs, io, marker::PhantomData, path::Path};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{CursorAction, SelectionMode};
+
+/// A single mouse button, key or gamepad button. Gamepad buttons match on
+/// any connected gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputButton {
+    Mouse(MouseButton),
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+}
+
+/// A button that triggers an action while every one of its modifiers is held.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chord {
+    #[serde(default)]
+    pub modifiers: Vec<InputButton>,
+    pub button: InputButton,
+}
+
+impl Chord {
+    pub fn new(button: InputButton) -> Self {
+        Self {
+            modifiers: Vec::new(),
+            button,
+        }
+    }
+
+    pub fn with_modifier(mut self, modifier: InputButton) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+}
+
+/// Every button the cursor responds to. Actions list alternative `Chord`s;
+/// modifiers list alternative buttons, any of which counts as held.
+///
+/// Insert before adding `CursorPlugin` to replace the defaults, e.g. with
+/// `CursorBindings::load("bindings.ron")`.
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CursorBindings {
+    pub select: Vec<Chord>,
+    pub command: Vec<Chord>,
+    pub cancel: Vec<Chord>,
+    /// Held at the start of a selection to add to it.
+    pub add: Vec<InputButton>,
+    /// Held at the start of a selection to toggle entities in and out of it.
+    pub toggle: Vec<InputButton>,
+    /// Held at the start of a selection to remove from it.
+    pub subtract: Vec<InputButton>,
+    /// Held on a click to select every visible entity sharing the clicked
+    /// entity's `SelectionKind`. Takes precedence over `toggle` for clicks.
+    pub same_kind: Vec<InputButton>,
+    /// Held with the command button to mark the order as queued.
+    pub queue: Vec<InputButton>,
+    /// Held when a box is released to select every `SelectionPriority` in it
+    /// rather than only the highest.
+    pub priority_override: Vec<InputButton>,
+    /// One button per control group; a button's position in the list is its
+    /// group index.
+    pub groups: Vec<InputButton>,
+    /// Held with a group button to replace the group with the selection.
+    pub group_assign: Vec<InputButton>,
+    /// Held with a group button to add the selection to the group.
+    pub group_append: Vec<InputButton>,
+}
+
+impl CursorBindings {
+    pub fn from_ron(ron: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(ron)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let ron = fs::read_to_string(path)?;
+        Self::from_ron(&ron).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let ron = self
+            .to_ron()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, ron)
+    }
+
+    fn chords(&self, action: CursorAction) -> &[Chord] {
+        match action {
+            CursorAction::Select => &self.select,
+            CursorAction::Command => &self.command,
+            CursorAction::Cancel => &self.cancel,
+        }
+    }
+}
+
+impl Default for CursorBindings {
+    fn default() -> Self {
+        let shift = vec![
+            InputButton::Key(KeyCode::LShift),
+            InputButton::Key(KeyCode::RShift),
+        ];
+        let control = vec![
+            InputButton::Key(KeyCode::LControl),
+            InputButton::Key(KeyCode::RControl),
+        ];
+        Self {
+            select: vec![
+                Chord::new(InputButton::Mouse(MouseButton::Left)),
+                Chord::new(InputButton::Gamepad(GamepadButtonType::South)),
+            ],
+            command: vec![
+                Chord::new(InputButton::Mouse(MouseButton::Right)),
+                Chord::new(InputButton::Gamepad(GamepadButtonType::East)),
+            ],
+            cancel: vec![
+                Chord::new(InputButton::Key(KeyCode::Escape)),
+                Chord::new(InputButton::Gamepad(GamepadButtonType::North)),
+            ],
+            add: shift.clone(),
+            toggle: control.clone(),
+            subtract: vec![
+                InputButton::Key(KeyCode::LAlt),
+                InputButton::Key(KeyCode::RAlt),
+            ],
+            same_kind: control.clone(),
+            queue: shift.clone(),
+            priority_override: vec![InputButton::Key(KeyCode::Grave)],
+            groups: vec![
+                InputButton::Key(KeyCode::Key1),
+                InputButton::Key(KeyCode::Key2),
+                InputButton::Key(KeyCode::Key3),
+                InputButton::Key(KeyCode::Key4),
+                InputButton::Key(KeyCode::Key5),
+                InputButton::Key(KeyCode::Key6),
+                InputButton::Key(KeyCode::Key7),
+                InputButton::Key(KeyCode::Key8),
+                InputButton::Key(KeyCode::Key9),
+            ],
+            group_assign: control,
+            group_append: shift,
+        }
+    }
+}
+
+/// The raw button state of every device, read through `CursorBindings`.
+#[derive(SystemParam)]
+pub struct BoundInput<'w, 's> {
+    pub bindings: Res<'w, CursorBindings>,
+    mouse: Res<'w, Input<MouseButton>>,
+    keyboard: Res<'w, Input<KeyCode>>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    gamepads: Res<'w, Gamepads>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> BoundInput<'w, 's> {
+    pub fn pressed(&self, button: InputButton) -> bool {
+        match button {
+            InputButton::Mouse(button) => self.mouse.pressed(button),
+            InputButton::Key(key) => self.keyboard.pressed(key),
+            InputButton::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, button_type))
+            }),
+        }
+    }
+
+    pub fn just_pressed(&self, button: InputButton) -> bool {
+        match button {
+            InputButton::Mouse(button) => self.mouse.just_pressed(button),
+            InputButton::Key(key) => self.keyboard.just_pressed(key),
+            InputButton::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| {
+                self.gamepad_buttons
+                    .just_pressed(GamepadButton::new(gamepad, button_type))
+            }),
+        }
+    }
+
+    pub fn any_pressed(&self, buttons: &[InputButton]) -> bool {
+        buttons.iter().any(|button| self.pressed(*button))
+    }
+
+    pub fn chord_just_pressed(&self, chord: &Chord) -> bool {
+        self.just_pressed(chord.button)
+            && chord
+                .modifiers
+                .iter()
+                .all(|modifier| self.pressed(*modifier))
+    }
+
+    /// The `SelectionMode` picked by the modifiers currently held.
+    pub fn selection_mode(&self) -> SelectionMode {
+        if self.any_pressed(&self.bindings.toggle) {
+            SelectionMode::Toggle
+        } else if self.any_pressed(&self.bindings.subtract) {
+            SelectionMode::Subtract
+        } else if self.any_pressed(&self.bindings.add) {
+            SelectionMode::Add
+        } else {
+            SelectionMode::Replace
+        }
+    }
+}
+
+/// Presses a `CursorAction` when one of its chords is pressed and releases
+/// it once none of their buttons are held. Only releases actions it pressed
+/// itself, leaving those pressed by touch alone.
+pub fn binding_input_system(
+    input: BoundInput,
+    mut actions: ResMut<Input<CursorAction>>,
+    mut held: Local<HashSet<CursorAction>>,
+) {
+    for action in [
+        CursorAction::Select,
+        CursorAction::Command,
+        CursorAction::Cancel,
+    ] {
+        let chords = input.bindings.chords(action);
+        if !held.contains(&action) && chords.iter().any(|chord| input.chord_just_pressed(chord)) {
+            actions.press(action);
+            held.insert(action);
+        }
+        if held.contains(&action) && !chords.iter().any(|chord| input.pressed(chord.button)) {
+            actions.release(action);
+            held.remove(&action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn middle_click_layout() -> CursorBindings {
+        CursorBindings {
+            command: vec![Chord::new(InputButton::Mouse(MouseButton::Middle))
+                .with_modifier(InputButton::Key(KeyCode::LAlt))],
+            groups: vec![InputButton::Key(KeyCode::F1), InputButton::Key(KeyCode::F2)],
+            ..default()
+        }
+    }
+
+    #[test]
+    fn ron_round_trips() {
+        let bindings = middle_click_layout();
+        let ron = bindings.to_ron().unwrap();
+        let loaded = CursorBindings::from_ron(&ron).unwrap();
+
+        assert_eq!(loaded.command, bindings.command);
+        assert_eq!(loaded.groups, bindings.groups);
+        assert_eq!(loaded.to_ron().unwrap(), ron);
+    }
+
+    #[test]
+    fn save_then_load() {
+        let path = std::env::temp_dir().join(format!("bindings-{}.ron", std::process::id()));
+        let bindings = middle_click_layout();
+        bindings.save(&path).unwrap();
+        let loaded = CursorBindings::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            loaded.unwrap().to_ron().unwrap(),
+            bindings.to_ron().unwrap()
+        );
+    }
+
+    #[test]
+    fn partial_file_falls_back_to_defaults() {
+        let loaded = CursorBindings::from_ron("(command: [(button: Mouse(Middle))])").unwrap();
+        let defaults = CursorBindings::default();
+
+        assert_eq!(
+            loaded.command,
+            vec![Chord::new(InputButton::Mouse(MouseButton::Middle))]
+        );
+        assert_eq!(loaded.select, defaults.select);
+        assert_eq!(loaded.cancel, defaults.cancel);
+        assert_eq!(loaded.groups, defaults.groups);
+    }
+
+    #[test]
+    fn invalid_file_is_an_error() {
+        assert!(CursorBindings::from_ron("(command: [(button: Nose)])").is_err());
+    }
+}
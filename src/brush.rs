@@ -0,0 +1,83 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+
+use crate::{
+    util::{is_position_in_circle, OUTLINE_HEIGHT},
+    Cursor, CursorGesture, Pickable, SelectionShape,
+};
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct BrushOutline;
+
+/// Paints circles of `CursorPlugin::brush_radius` along the ground under the
+/// cursor from the moment a brush is pressed, collecting every `Pickable` they cover
+/// into `Cursor::brushed`.
+#[allow(clippy::type_complexity)]
+pub fn brush_system(
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    pickables: Query<(Entity, &GlobalTransform), With<Pickable>>,
+    mut outlines: Query<(Entity, &mut Transform), With<BrushOutline>>,
+    mut last_location: Local<Option<Vec3>>,
+) {
+    if cursor.settings.selection_shape != SelectionShape::Brush
+        || cursor.gesture != CursorGesture::Dragging
+    {
+        for (entity, _) in &outlines {
+            commands.entity(entity).despawn_recursive();
+        }
+        *last_location = None;
+        return;
+    }
+
+    let radius = cursor.settings.brush_radius;
+    let tolerance = cursor.settings.y_inclusion_limit;
+
+    // Step along the path since last frame so fast strokes leave no gaps.
+    let from = last_location.unwrap_or(cursor.pressed_location);
+    let to = cursor.location;
+    let steps = ((to - from).length() / (radius / 2.).max(f32::EPSILON)).ceil() as usize;
+    for step in 0..=steps {
+        let center = from.lerp(to, step as f32 / steps.max(1) as f32);
+        for (entity, transform) in &pickables {
+            if is_position_in_circle(transform.translation(), center, radius, tolerance) {
+                cursor.brushed.insert(entity);
+            }
+        }
+    }
+    *last_location = Some(to);
+
+    let translation = to + Vec3::Y * OUTLINE_HEIGHT;
+    if let Ok((_, mut transform)) = outlines.get_single_mut() {
+        transform.translation = translation;
+        return;
+    }
+
+    let aesthetics = &cursor.settings.aesthetics;
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Torus {
+                radius,
+                ring_radius: aesthetics.line_thickness / 2.,
+                ..default()
+            })),
+            material: materials.add(StandardMaterial {
+                alpha_mode: AlphaMode::Blend,
+                base_color: aesthetics.bounding_box_color,
+                emissive: aesthetics.bounding_box_color,
+                ..default()
+            }),
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        NotShadowReceiver,
+        NotShadowCaster,
+        BrushOutline,
+        Name::new("BrushOutline"),
+    ));
+}
@@ -0,0 +1,136 @@
+use bevy::{ecs::system::Command, hierarchy::despawn_with_children_recursive, prelude::*};
+
+use crate::{ControlGroups, Cursor, Pickable, SelectionCause, SelectionEvents, SelectionMode};
+
+/// Drops entities that were despawned, or are no longer `Pickable`, from the
+/// selection and every control group, sending `SelectionCause::Despawn`
+/// events for those that were selected.
+pub fn prune_despawned_system(
+    mut cursor: ResMut<Cursor>,
+    mut control_groups: ResMut<ControlGroups>,
+    pickables: Query<(), With<Pickable>>,
+    mut events: SelectionEvents,
+) {
+    let gone: Vec<Entity> = cursor
+        .selection
+        .selected_units
+        .iter()
+        .copied()
+        .filter(|entity| !pickables.contains(*entity))
+        .collect();
+    if !gone.is_empty() {
+        let delta = cursor.selection.apply(SelectionMode::Subtract, gone);
+        events.send(delta, SelectionCause::Despawn);
+    }
+
+    let selection = &mut cursor.selection;
+    selection
+        .snapshot
+        .retain(|entity| pickables.contains(*entity));
+    selection
+        .preview
+        .retain(|entity| pickables.contains(*entity));
+    if selection
+        .last_click
+        .is_some_and(|(entity, _)| !pickables.contains(entity))
+    {
+        selection.last_click = None;
+    }
+    if cursor
+        .hovered
+        .is_some_and(|entity| !pickables.contains(entity))
+    {
+        cursor.hovered = None;
+    }
+
+    for group in control_groups.groups.values_mut() {
+        group.retain(|entity| pickables.contains(*entity));
+    }
+}
+
+/// Inserts `bundle` into `entity` if it still exists when commands are
+/// applied. `EntityCommands::insert` panics on entities despawned earlier in
+/// the same stage, e.g. a unit that dies the frame it is selected.
+pub struct TryInsert<B> {
+    pub entity: Entity,
+    pub bundle: B,
+}
+
+impl<B: Bundle> Command for TryInsert<B> {
+    fn write(self, world: &mut World) {
+        if let Some(mut entity) = world.get_entity_mut(self.entity) {
+            entity.insert(self.bundle);
+        }
+    }
+}
+
+/// Parents `child` to `parent`, or despawns `child` if `parent` was despawned
+/// before commands are applied.
+pub struct TryAddChild {
+    pub parent: Entity,
+    pub child: Entity,
+}
+
+impl Command for TryAddChild {
+    fn write(self, world: &mut World) {
+        if let Some(mut parent) = world.get_entity_mut(self.parent) {
+            parent.push_children(&[self.child]);
+        } else {
+            despawn_with_children_recursive(world, self.child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+    use crate::Selected;
+
+    #[test]
+    fn commands_on_despawned_entities_are_dropped() {
+        let mut world = World::new();
+        let unit = world.spawn_empty().id();
+        let ring = world.spawn_empty().id();
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.add(TryInsert {
+            entity: unit,
+            bundle: Selected,
+        });
+        commands.add(TryAddChild {
+            parent: unit,
+            child: ring,
+        });
+        // A game system kills the unit before the commands are applied.
+        world.despawn(unit);
+        queue.apply(&mut world);
+
+        assert!(world.get_entity(unit).is_none());
+        assert!(world.get_entity(ring).is_none());
+    }
+
+    #[test]
+    fn commands_on_live_entities_apply() {
+        let mut world = World::new();
+        let unit = world.spawn_empty().id();
+        let ring = world.spawn_empty().id();
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.add(TryInsert {
+            entity: unit,
+            bundle: Selected,
+        });
+        commands.add(TryAddChild {
+            parent: unit,
+            child: ring,
+        });
+        queue.apply(&mut world);
+
+        assert!(world.entity(unit).contains::<Selected>());
+        assert_eq!(world.get::<Parent>(ring).map(Parent::get), Some(unit));
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::{
+    formation::{assign_slots, formation_slots, FormationCommand, Ground},
+    Allegiances, BoundInput, Cursor, CursorAction,
+};
+
+/// An order issued to the selected entities with the command button.
+#[derive(Debug, Clone)]
+pub struct CursorCommand {
+    /// The selected entities receiving the order, sorted.
+    pub targets: Vec<Entity>,
+    /// The point on the `CursorReflector` surface under the cursor.
+    pub point: Vec3,
+    /// The `Pickable` under the cursor, if any.
+    pub target_entity: Option<Entity>,
+    /// Whether the order should be queued after the targets' current orders.
+    pub queued: bool,
+}
+
+/// Issues a `CursorCommand` when the command button is clicked, or a
+/// `FormationCommand` when it is dragged.
+#[allow(clippy::too_many_arguments)]
+pub fn command_system(
+    mut cursor: ResMut<Cursor>,
+    actions: Res<Input<CursorAction>>,
+    input: BoundInput,
+    transforms: Query<&GlobalTransform>,
+    allegiances: Allegiances,
+    ground: Ground,
+    mut orders: EventWriter<CursorCommand>,
+    mut formations: EventWriter<FormationCommand>,
+) {
+    let settings = cursor.settings.command.clone();
+    // The command button cancels a held select rather than ordering.
+    if actions.just_pressed(CursorAction::Command) && !actions.pressed(CursorAction::Select) {
+        cursor.command_pressed_location = Some(cursor.location);
+    }
+    if !actions.just_released(CursorAction::Command) {
+        return;
+    }
+    let Some(pressed_location) = cursor.command_pressed_location.take() else {
+        return;
+    };
+
+    // Inspected enemies can't be ordered around.
+    let mut targets: Vec<Entity> = cursor
+        .selection
+        .selected_units
+        .iter()
+        .copied()
+        .filter(|&entity| allegiances.is_orderable(entity))
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+    targets.sort();
+    let queued = input.any_pressed(&input.bindings.queue);
+
+    let drag = cursor.location - pressed_location;
+    if Vec2::new(drag.x, drag.z).length() < cursor.settings.click_threshold {
+        orders.send(CursorCommand {
+            targets,
+            point: cursor.location,
+            target_entity: cursor.hovered,
+            queued,
+        });
+    } else {
+        let (facing, slots) = formation_slots(
+            pressed_location,
+            drag,
+            targets.len(),
+            settings.formation_spacing,
+        );
+        let slots: Vec<Vec3> = slots.into_iter().map(|slot| ground.snap(slot)).collect();
+        let units: Vec<(Entity, Vec3)> = targets
+            .iter()
+            .map(|&entity| {
+                let position = transforms
+                    .get(entity)
+                    .map_or(pressed_location, |transform| transform.translation());
+                (entity, position)
+            })
+            .collect();
+        formations.send(FormationCommand {
+            targets: assign_slots(&units, &slots, facing),
+            queued,
+        });
+    }
+}
@@ -11,3 +11,37 @@ pub struct SelectionHighlighter;
 #[derive(Component, Reflect, Default, Debug)]
 #[reflect(Component)]
 pub struct Selected;
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct Hovered;
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct HoverHighlighter;
+
+/// On entities inside the selection box being dragged, which would be
+/// selected if it were released now.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct Previewed;
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct PreviewHighlighter;
+
+/// Groups entities for double-click "select all of this kind".
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub struct SelectionKind(pub u32);
+
+/// The player an entity belongs to. Entities without one are neutral.
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub struct Owner(pub u32);
+
+/// When a box catches entities of several priorities, only those with the
+/// highest are selected. Entities without one have priority 0.
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[reflect(Component)]
+pub struct SelectionPriority(pub i32);
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate::{Allegiance, Allegiances, Cursor, CursorGesture};
+
+/// The situation the cursor is in, used to pick icons and world cursors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, FromReflect)]
+pub enum CursorContext {
+    #[default]
+    OverGround,
+    /// Hovering any `Pickable` not owned by another player.
+    OverOwnUnit,
+    /// Hovering a `Pickable` owned by another player.
+    OverEnemy,
+    /// A selection box is being dragged.
+    Dragging,
+    /// The cursor is outside `CursorPlugin::bounds`.
+    OutOfBounds,
+    /// Hovering the ground with a selection that can be ordered.
+    Command,
+}
+
+pub fn cursor_context_system(mut cursor: ResMut<Cursor>, allegiances: Allegiances) {
+    let context = if cursor.gesture == CursorGesture::Dragging {
+        CursorContext::Dragging
+    } else if !cursor.in_bounds {
+        CursorContext::OutOfBounds
+    } else if let Some(hovered) = cursor.hovered {
+        match allegiances.of(hovered) {
+            Allegiance::Enemy => CursorContext::OverEnemy,
+            _ => CursorContext::OverOwnUnit,
+        }
+    } else if cursor
+        .selection
+        .selected_units
+        .iter()
+        .any(|&entity| allegiances.is_orderable(entity))
+    {
+        CursorContext::Command
+    } else {
+        CursorContext::OverGround
+    };
+
+    if cursor.context != context {
+        cursor.context = context;
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::resources::SelectionDelta;
+
+/// What caused entities to enter or leave the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionCause {
+    Box,
+    Click,
+    /// A replacing box or click that caught nothing.
+    Clear,
+    Cancel,
+    ControlGroup,
+    Despawn,
+}
+
+/// Sent once per change to `Cursor::selection` with every entity that
+/// entered or left it.
+#[derive(Debug, Clone)]
+pub struct SelectionChanged {
+    pub added: Vec<Entity>,
+    pub removed: Vec<Entity>,
+    pub cause: SelectionCause,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EntitySelected {
+    pub entity: Entity,
+    pub cause: SelectionCause,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EntityDeselected {
+    pub entity: Entity,
+    pub cause: SelectionCause,
+}
+
+/// Sent when an in-progress selection box is cancelled, after the selection
+/// held before the press has been restored.
+#[derive(Debug, Clone)]
+pub struct SelectionCancelled {
+    /// The restored selection, sorted.
+    pub selection: Vec<Entity>,
+}
+
+#[derive(SystemParam)]
+pub struct SelectionEvents<'w, 's> {
+    changed: EventWriter<'w, 's, SelectionChanged>,
+    selected: EventWriter<'w, 's, EntitySelected>,
+    deselected: EventWriter<'w, 's, EntityDeselected>,
+    cancelled: EventWriter<'w, 's, SelectionCancelled>,
+}
+
+impl<'w, 's> SelectionEvents<'w, 's> {
+    pub fn send(&mut self, delta: SelectionDelta, cause: SelectionCause) {
+        if delta.is_empty() {
+            return;
+        }
+
+        for &entity in &delta.removed {
+            self.deselected.send(EntityDeselected { entity, cause });
+        }
+        for &entity in &delta.added {
+            self.selected.send(EntitySelected { entity, cause });
+        }
+        self.changed.send(SelectionChanged {
+            added: delta.added,
+            removed: delta.removed,
+            cause,
+        });
+    }
+
+    pub fn send_cancelled(&mut self, delta: SelectionDelta, selection: Vec<Entity>) {
+        self.send(delta, SelectionCause::Cancel);
+        self.cancelled.send(SelectionCancelled { selection });
+    }
+}
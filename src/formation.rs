@@ -0,0 +1,174 @@
+use bevy::{
+    ecs::system::SystemParam,
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+use bevy_mod_raycast::{ray_intersection_over_mesh, Backfaces, Ray3d, RaycastMesh};
+
+use crate::{waypoints::WaypointAssets, Allegiances, Cursor, CursorAction, RayReflector};
+
+/// How far above a slot the `CursorReflector` surface is searched for.
+const GROUND_PROBE_HEIGHT: f32 = 1000.;
+
+/// An order placing the commanded entities in a line, sent when the command
+/// button is dragged instead of clicked.
+#[derive(Debug, Clone)]
+pub struct FormationCommand {
+    /// One slot per commanded entity, sorted by entity.
+    pub targets: Vec<FormationTarget>,
+    pub queued: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FormationTarget {
+    pub entity: Entity,
+    pub position: Vec3,
+    /// Horizontal unit vector the entity should face on arrival.
+    pub facing: Vec3,
+}
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct FormationGhost;
+
+/// The `CursorReflector` meshes, for dropping formation slots onto uneven
+/// ground.
+#[derive(SystemParam)]
+pub struct Ground<'w, 's> {
+    reflectors: Query<
+        'w,
+        's,
+        (&'static Handle<Mesh>, &'static GlobalTransform),
+        With<RaycastMesh<RayReflector>>,
+    >,
+    meshes: Res<'w, Assets<Mesh>>,
+}
+
+impl<'w, 's> Ground<'w, 's> {
+    /// `point` moved straight up or down onto the nearest `CursorReflector`
+    /// surface below `GROUND_PROBE_HEIGHT`, or left as is over no surface.
+    pub fn snap(&self, point: Vec3) -> Vec3 {
+        let ray = Ray3d::new(point + Vec3::Y * GROUND_PROBE_HEIGHT, Vec3::NEG_Y);
+        self.reflectors
+            .iter()
+            .filter_map(|(handle, transform)| {
+                let mesh = self.meshes.get(handle)?;
+                ray_intersection_over_mesh(mesh, &transform.compute_matrix(), &ray, Backfaces::Cull)
+            })
+            .map(|hit| hit.position())
+            .min_by(|a, b| (a.y - point.y).abs().total_cmp(&(b.y - point.y).abs()))
+            .unwrap_or(point)
+    }
+}
+
+/// Lays out `count` slots in rows centered on `center`. The rows face along
+/// `drag` and are as wide as `drag` is long; extra rows fill in behind. Slots
+/// are level with `center`; drop them onto uneven ground with `Ground::snap`.
+pub fn formation_slots(center: Vec3, drag: Vec3, count: usize, spacing: f32) -> (Vec3, Vec<Vec3>) {
+    let facing = Vec3::new(drag.x, 0., drag.z).normalize_or_zero();
+    let right = facing.cross(Vec3::Y);
+    let width = Vec3::new(drag.x, 0., drag.z).length();
+
+    let columns = ((width / spacing).floor() as usize + 1).clamp(1, count.max(1));
+    let mut slots = Vec::with_capacity(count);
+    for row in 0..count.div_ceil(columns) {
+        let in_row = columns.min(count - row * columns);
+        for column in 0..in_row {
+            let lateral = (column as f32 - (in_row - 1) as f32 / 2.) * spacing;
+            slots.push(center + right * lateral - facing * row as f32 * spacing);
+        }
+    }
+    (facing, slots)
+}
+
+/// Pairs entities with slots by their order across the line so that paths
+/// cross as little as possible. Ties are broken by entity for determinism.
+pub fn assign_slots(
+    units: &[(Entity, Vec3)],
+    slots: &[Vec3],
+    facing: Vec3,
+) -> Vec<FormationTarget> {
+    let right = facing.cross(Vec3::Y);
+    let lateral = |position: &Vec3| position.dot(right);
+
+    let mut units = units.to_vec();
+    units.sort_by(|(a_entity, a), (b_entity, b)| {
+        lateral(a)
+            .total_cmp(&lateral(b))
+            .then(a_entity.cmp(b_entity))
+    });
+    let mut slots = slots.to_vec();
+    slots.sort_by(|a, b| lateral(a).total_cmp(&lateral(b)));
+
+    let mut targets: Vec<FormationTarget> = units
+        .iter()
+        .zip(slots)
+        .map(|(&(entity, _), position)| FormationTarget {
+            entity,
+            position,
+            facing,
+        })
+        .collect();
+    targets.sort_by_key(|target| target.entity);
+    targets
+}
+
+/// Previews the slots of the formation being dragged out, reusing the ghosts
+/// from the previous frame.
+#[allow(clippy::too_many_arguments)]
+pub fn formation_preview_system(
+    mut commands: Commands,
+    cursor: Res<Cursor>,
+    actions: Res<Input<CursorAction>>,
+    waypoint_assets: Res<WaypointAssets>,
+    mut ghosts: Query<(Entity, &mut Transform), With<FormationGhost>>,
+    allegiances: Allegiances,
+    ground: Ground,
+) {
+    let drag = cursor
+        .command_pressed_location
+        .map(|pressed_location| (pressed_location, cursor.location - pressed_location));
+    let count = cursor
+        .selection
+        .selected_units
+        .iter()
+        .filter(|&&entity| allegiances.is_orderable(entity))
+        .count();
+    let slots = match drag {
+        Some((pressed_location, drag))
+            if actions.pressed(CursorAction::Command)
+                && count > 0
+                && Vec2::new(drag.x, drag.z).length() >= cursor.settings.click_threshold =>
+        {
+            formation_slots(
+                pressed_location,
+                drag,
+                count,
+                cursor.settings.command.formation_spacing,
+            )
+            .1
+        }
+        _ => Vec::new(),
+    };
+
+    let mut slots = slots.into_iter().map(|slot| ground.snap(slot));
+    for (entity, mut transform) in &mut ghosts {
+        match slots.next() {
+            Some(slot) => transform.translation = slot,
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+    for slot in slots {
+        commands.spawn((
+            PbrBundle {
+                mesh: waypoint_assets.marker_mesh.clone(),
+                material: waypoint_assets.ghost_material.clone(),
+                transform: Transform::from_translation(slot),
+                ..default()
+            },
+            NotShadowReceiver,
+            NotShadowCaster,
+            FormationGhost,
+        ));
+    }
+}
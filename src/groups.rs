@@ -0,0 +1,90 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::{
+    limit_selection, BoundInput, Cursor, RayReflector, RaycastSource, SelectionCause,
+    SelectionEvents, SelectionMode, SelectionPriority,
+};
+
+/// Selections stored under the number keys, indexed by their position in
+/// `CursorBindings::groups`.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct ControlGroups {
+    pub groups: HashMap<usize, HashSet<Entity>>,
+    last_recall: Option<(usize, f64)>,
+}
+
+/// Sent when a control group is recalled twice within
+/// `ControlGroupSettings::double_tap_time`, e.g. to move the camera to it.
+#[derive(Debug, Clone)]
+pub struct FocusControlGroup {
+    pub group: usize,
+    pub entities: Vec<Entity>,
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn control_group_system(
+    mut cursor: ResMut<Cursor>,
+    mut control_groups: ResMut<ControlGroups>,
+    input: BoundInput,
+    time: Res<Time>,
+    transforms: Query<&GlobalTransform>,
+    cameras: Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+    priorities: Query<&SelectionPriority>,
+    mut events: SelectionEvents,
+    mut focus: EventWriter<FocusControlGroup>,
+) {
+    let settings = cursor.settings.control_groups.clone();
+    let Some(group) = input
+        .bindings
+        .groups
+        .iter()
+        .position(|button| input.just_pressed(*button))
+    else {
+        return;
+    };
+
+    if input.any_pressed(&input.bindings.group_assign) {
+        control_groups
+            .groups
+            .insert(group, cursor.selection.selected_units.clone());
+    } else if input.any_pressed(&input.bindings.group_append) {
+        control_groups
+            .groups
+            .entry(group)
+            .or_default()
+            .extend(cursor.selection.selected_units.iter().copied());
+    } else {
+        // Recalling an unassigned or emptied group leaves the selection be.
+        let Some(members) = control_groups
+            .groups
+            .get(&group)
+            .filter(|members| !members.is_empty())
+            .cloned()
+        else {
+            return;
+        };
+        let members = limit_selection(
+            &cursor,
+            members.into_iter().collect(),
+            cursor.screen_location,
+            &transforms,
+            &cameras,
+            &priorities,
+        );
+        let delta = cursor.selection.apply(SelectionMode::Replace, members);
+        events.send(delta, SelectionCause::ControlGroup);
+
+        let now = time.elapsed_seconds_f64();
+        if let Some((last_group, last_time)) = control_groups.last_recall {
+            if last_group == group && now - last_time <= settings.double_tap_time as f64 {
+                let mut entities: Vec<Entity> =
+                    cursor.selection.selected_units.iter().copied().collect();
+                entities.sort();
+                focus.send(FocusControlGroup { group, entities });
+            }
+        }
+        control_groups.last_recall = Some((group, now));
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use crate::{Cursor, CursorContext};
+
+/// OS cursor icons for each `CursorContext`, applied to the primary window.
+#[derive(Resource, Debug, Clone)]
+pub struct CursorIconRules {
+    pub enabled: bool,
+    pub dragging: CursorIcon,
+    pub out_of_bounds: CursorIcon,
+    pub over_enemy: CursorIcon,
+    pub over_own_unit: CursorIcon,
+    pub command_mode: CursorIcon,
+    pub over_ground: CursorIcon,
+}
+
+impl CursorIconRules {
+    pub fn icon(&self, context: CursorContext) -> CursorIcon {
+        match context {
+            CursorContext::Dragging => self.dragging,
+            CursorContext::OutOfBounds => self.out_of_bounds,
+            CursorContext::OverEnemy => self.over_enemy,
+            CursorContext::OverOwnUnit => self.over_own_unit,
+            CursorContext::Command => self.command_mode,
+            CursorContext::OverGround => self.over_ground,
+        }
+    }
+}
+
+impl Default for CursorIconRules {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dragging: CursorIcon::Cell,
+            out_of_bounds: CursorIcon::NotAllowed,
+            over_enemy: CursorIcon::Crosshair,
+            over_own_unit: CursorIcon::Hand,
+            command_mode: CursorIcon::Move,
+            over_ground: CursorIcon::Default,
+        }
+    }
+}
+
+pub fn cursor_icon_system(
+    cursor: Res<Cursor>,
+    rules: Res<CursorIconRules>,
+    mut windows: ResMut<Windows>,
+) {
+    if !rules.enabled {
+        return;
+    }
+
+    let icon = rules.icon(cursor.context);
+    if let Some(window) = windows.get_primary_mut() {
+        if window.cursor_icon() != icon {
+            window.set_cursor_icon(icon);
+        }
+    }
+}
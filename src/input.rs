@@ -0,0 +1,633 @@
+use std::{
+    collections::BTreeMap,
+    f32::consts::{PI, TAU},
+};
+
+use bevy::{
+    input::touch::{TouchInput, TouchPhase},
+    prelude::*,
+    render::camera::Camera,
+};
+
+use crate::{Cursor, Pickable, RayReflector, RaycastSource};
+
+/// Device independent cursor interactions, read through
+/// `Res<Input<CursorAction>>`. Buttons bound in `CursorBindings` and touch
+/// input are translated into these so selection and commands don't depend on
+/// the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorAction {
+    Select,
+    Command,
+    Cancel,
+}
+
+/// Sent every frame two or more fingers move together, for camera control.
+#[derive(Debug, Clone, Copy)]
+pub struct TouchGesture {
+    pub touches: usize,
+    /// Movement of the fingers' centroid in screen pixels.
+    pub pan: Vec2,
+    /// Ratio of the fingers' spread to the previous frame's.
+    pub pinch: f32,
+    /// Change in angle, in radians, between the first two fingers.
+    pub rotation: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PrimaryTouch {
+    id: u64,
+    start: Vec2,
+    start_time: f64,
+    dragging: bool,
+    finished: bool,
+}
+
+#[derive(Default)]
+pub struct TouchState {
+    positions: BTreeMap<u64, Vec2>,
+    primary: Option<PrimaryTouch>,
+    pending_position: Option<Vec2>,
+    previous_gesture: Option<(Vec2, f32, f32)>,
+}
+
+#[derive(Default)]
+pub struct GamepadCursorState {
+    held_time: f32,
+    /// Where the gamepad last left the cursor. Any other position means the
+    /// mouse or a touch has moved it since.
+    last_location: Option<Vec2>,
+}
+
+pub fn clear_cursor_actions(mut actions: ResMut<Input<CursorAction>>) {
+    actions.clear();
+}
+
+pub fn mouse_input_system(mut cursor: ResMut<Cursor>, mut cursor_moved: EventReader<CursorMoved>) {
+    if let Some(cursor_moved) = cursor_moved.iter().last() {
+        cursor.screen_location = cursor_moved.position;
+    }
+}
+
+/// Translates a single finger into cursor actions: a tap selects, a drag
+/// draws a selection box and a long press commands. Two or more fingers are
+/// reported as `TouchGesture`s instead.
+pub fn touch_input_system(
+    mut cursor: ResMut<Cursor>,
+    mut actions: ResMut<Input<CursorAction>>,
+    mut touches: EventReader<TouchInput>,
+    mut gestures: EventWriter<TouchGesture>,
+    mut state: Local<TouchState>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+) {
+    // Catch up with a drag that was held at its start point for a frame.
+    if let Some(position) = state.pending_position.take() {
+        cursor.screen_location = position;
+    }
+
+    let height = windows.get_primary().map_or(0., |window| window.height());
+    let settings = cursor.settings.touch.clone();
+    let now = time.elapsed_seconds_f64();
+
+    for touch in touches.iter() {
+        // Touches have their origin at the top left, unlike the cursor.
+        let position = Vec2::new(touch.position.x, height - touch.position.y);
+
+        match touch.phase {
+            TouchPhase::Started => {
+                state.positions.insert(touch.id, position);
+                if state.positions.len() == 1 {
+                    state.primary = Some(PrimaryTouch {
+                        id: touch.id,
+                        start: position,
+                        start_time: now,
+                        dragging: false,
+                        finished: false,
+                    });
+                    cursor.screen_location = position;
+                } else if let Some(primary) = &mut state.primary {
+                    // A second finger turns an undecided touch into a gesture.
+                    if !primary.dragging {
+                        primary.finished = true;
+                    }
+                }
+            }
+            TouchPhase::Moved => {
+                state.positions.insert(touch.id, position);
+                let Some(primary) = &mut state.primary else {
+                    continue;
+                };
+                if primary.id != touch.id || primary.finished {
+                    continue;
+                }
+
+                if primary.dragging {
+                    cursor.screen_location = position;
+                } else if primary.start.distance(position) > settings.drag_threshold {
+                    // Press where the finger went down so the box starts
+                    // there, then follow the finger from the next frame.
+                    primary.dragging = true;
+                    cursor.screen_location = primary.start;
+                    state.pending_position = Some(position);
+                    actions.press(CursorAction::Select);
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                state.positions.remove(&touch.id);
+                let Some(primary) = state.primary else {
+                    continue;
+                };
+                if primary.id != touch.id {
+                    continue;
+                }
+
+                if primary.dragging {
+                    state.pending_position = None;
+                    if touch.phase == TouchPhase::Cancelled {
+                        // The system took the finger away: drop the box
+                        // rather than selecting with it.
+                        actions.press(CursorAction::Cancel);
+                        actions.release(CursorAction::Cancel);
+                    } else {
+                        cursor.screen_location = position;
+                    }
+                    actions.release(CursorAction::Select);
+                } else if !primary.finished && touch.phase == TouchPhase::Ended {
+                    cursor.screen_location = position;
+                    actions.press(CursorAction::Select);
+                    actions.release(CursorAction::Select);
+                }
+                state.primary = None;
+            }
+        }
+    }
+
+    if let Some(primary) = &mut state.primary {
+        if !primary.dragging
+            && !primary.finished
+            && now - primary.start_time >= settings.long_press_time as f64
+        {
+            primary.finished = true;
+            actions.press(CursorAction::Command);
+            actions.release(CursorAction::Command);
+        }
+    }
+
+    report_gestures(&mut state, &mut gestures);
+}
+
+fn report_gestures(state: &mut TouchState, gestures: &mut EventWriter<TouchGesture>) {
+    if state.positions.len() < 2 {
+        state.previous_gesture = None;
+        return;
+    }
+
+    let touches = state.positions.len();
+    let centroid = state.positions.values().copied().sum::<Vec2>() / touches as f32;
+    let spread = state
+        .positions
+        .values()
+        .map(|position| position.distance(centroid))
+        .sum::<f32>()
+        / touches as f32;
+    let mut positions = state.positions.values();
+    let (first, second) = (positions.next().unwrap(), positions.next().unwrap());
+    let angle = (*second - *first).y.atan2((*second - *first).x);
+
+    if let Some((previous_centroid, previous_spread, previous_angle)) = state.previous_gesture {
+        let pan = centroid - previous_centroid;
+        let pinch = if previous_spread > 0. {
+            spread / previous_spread
+        } else {
+            1.
+        };
+        let rotation = (angle - previous_angle + PI).rem_euclid(TAU) - PI;
+        if pan != Vec2::ZERO || pinch != 1. || rotation != 0. {
+            gestures.send(TouchGesture {
+                touches,
+                pan,
+                pinch,
+                rotation,
+            });
+        }
+    }
+    state.previous_gesture = Some((centroid, spread, angle));
+}
+
+/// Moves a virtual cursor with a gamepad stick, accelerating while the stick
+/// is held and drifting onto the nearest `Pickable` on screen when it is
+/// released. Its buttons are bound in `CursorBindings`; holding select while
+/// moving draws a selection box.
+#[allow(clippy::too_many_arguments)]
+pub fn gamepad_input_system(
+    mut cursor: ResMut<Cursor>,
+    mut state: Local<GamepadCursorState>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+    cameras: Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+    pickables: Query<&GlobalTransform, With<Pickable>>,
+) {
+    let settings = cursor.settings.gamepad.clone();
+    if !settings.enabled {
+        return;
+    }
+    let Some(gamepad) = gamepads.iter().next() else {
+        return;
+    };
+
+    let stick = Vec2::new(
+        axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or(0.),
+        axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or(0.),
+    );
+    let delta_seconds = time.delta_seconds();
+
+    if stick.length() > settings.dead_zone {
+        state.held_time += delta_seconds;
+        let speed = (settings.speed * (1. + settings.acceleration * state.held_time))
+            .min(settings.max_speed);
+        cursor.screen_location += stick * speed * delta_seconds;
+    } else if state.last_location == Some(cursor.screen_location) {
+        state.held_time = 0.;
+
+        // Aim assist: pull the idle cursor onto the closest target in reach.
+        let target = cameras
+            .iter()
+            .flat_map(|(camera, camera_transform)| {
+                pickables.iter().filter_map(|transform| {
+                    camera.world_to_viewport(camera_transform, transform.translation())
+                })
+            })
+            .filter(|point| point.distance(cursor.screen_location) <= settings.snap_radius)
+            .min_by(|a, b| {
+                a.distance(cursor.screen_location)
+                    .total_cmp(&b.distance(cursor.screen_location))
+            });
+        if let Some(target) = target {
+            let amount = (settings.snap_strength * delta_seconds).min(1.);
+            cursor.screen_location = cursor.screen_location.lerp(target, amount);
+        }
+    } else {
+        state.held_time = 0.;
+        return;
+    }
+
+    if let Some(window) = windows.get_primary() {
+        cursor.screen_location = cursor
+            .screen_location
+            .clamp(Vec2::ZERO, Vec2::new(window.width(), window.height()));
+    }
+    state.last_location = Some(cursor.screen_location);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        asset::AssetPlugin,
+        input::gamepad::{gamepad_connection_system, GamepadEvent, GamepadEventType, GamepadInfo},
+        render::camera::camera_system,
+        window::{WindowCreated, WindowId, WindowResized},
+    };
+
+    use super::*;
+    use crate::{
+        mouse_system, CursorBindings, CursorGesture, CursorPlugin, EntityDeselected,
+        EntitySelected, Pickable, SelectionCancelled, SelectionChanged,
+    };
+
+    fn touch_app() -> App {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin::default())
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .insert_resource(Cursor {
+                settings: CursorPlugin::default(),
+                ..default()
+            })
+            .insert_resource(Time::default())
+            .init_resource::<Windows>()
+            .init_resource::<Input<CursorAction>>()
+            .init_resource::<CursorBindings>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Gamepads>()
+            .add_event::<TouchInput>()
+            .add_event::<TouchGesture>()
+            .add_event::<SelectionChanged>()
+            .add_event::<EntitySelected>()
+            .add_event::<EntityDeselected>()
+            .add_event::<SelectionCancelled>()
+            .add_system_to_stage(CoreStage::First, clear_cursor_actions)
+            .add_system(touch_input_system)
+            .add_system(mouse_system.after(touch_input_system));
+        app
+    }
+
+    /// Runs a frame `seconds` after the previous one.
+    fn update(app: &mut App, seconds: f32) {
+        let mut time = app.world.resource_mut::<Time>();
+        let last = time.last_update().unwrap_or_else(|| time.startup());
+        time.update_with_instant(last + Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    fn touch(app: &mut App, id: u64, phase: TouchPhase, position: Vec2) {
+        app.world.send_event(TouchInput {
+            phase,
+            position,
+            force: None,
+            id,
+        });
+    }
+
+    fn gesture(app: &App) -> CursorGesture {
+        app.world.resource::<Cursor>().gesture
+    }
+
+    #[test]
+    fn tap_clicks() {
+        let mut app = touch_app();
+        touch(&mut app, 0, TouchPhase::Started, Vec2::new(100., 100.));
+        update(&mut app, 0.05);
+        touch(&mut app, 0, TouchPhase::Ended, Vec2::new(102., 101.));
+        update(&mut app, 0.05);
+
+        assert_eq!(gesture(&app), CursorGesture::Clicked);
+    }
+
+    #[test]
+    fn drag_past_threshold_draws_a_box() {
+        let mut app = touch_app();
+        let threshold = app.world.resource::<Cursor>().settings.touch.drag_threshold;
+        touch(&mut app, 0, TouchPhase::Started, Vec2::new(100., 100.));
+        update(&mut app, 0.05);
+        touch(
+            &mut app,
+            0,
+            TouchPhase::Moved,
+            Vec2::new(100. + threshold / 2., 100.),
+        );
+        update(&mut app, 0.05);
+        // Nothing is pressed until the finger passes the threshold.
+        assert_eq!(gesture(&app), CursorGesture::Idle);
+
+        touch(
+            &mut app,
+            0,
+            TouchPhase::Moved,
+            Vec2::new(100. + threshold * 4., 100.),
+        );
+        update(&mut app, 0.05);
+        update(&mut app, 0.05);
+        assert_eq!(gesture(&app), CursorGesture::Dragging);
+
+        touch(
+            &mut app,
+            0,
+            TouchPhase::Ended,
+            Vec2::new(100. + threshold * 4., 100.),
+        );
+        update(&mut app, 0.05);
+        assert_eq!(gesture(&app), CursorGesture::Dragged);
+    }
+
+    #[test]
+    fn cancelled_drag_cancels_the_box() {
+        let mut app = touch_app();
+        touch(&mut app, 0, TouchPhase::Started, Vec2::new(100., 100.));
+        update(&mut app, 0.05);
+        touch(&mut app, 0, TouchPhase::Moved, Vec2::new(200., 100.));
+        update(&mut app, 0.05);
+        update(&mut app, 0.05);
+        touch(&mut app, 0, TouchPhase::Cancelled, Vec2::new(200., 100.));
+        update(&mut app, 0.05);
+
+        assert_eq!(gesture(&app), CursorGesture::Cancelled);
+        assert!(!app
+            .world
+            .resource::<Input<CursorAction>>()
+            .pressed(CursorAction::Select));
+    }
+
+    #[test]
+    fn long_press_commands() {
+        let mut app = touch_app();
+        let long_press_time = app
+            .world
+            .resource::<Cursor>()
+            .settings
+            .touch
+            .long_press_time;
+        touch(&mut app, 0, TouchPhase::Started, Vec2::new(100., 100.));
+        update(&mut app, 0.05);
+        assert!(!app
+            .world
+            .resource::<Input<CursorAction>>()
+            .just_pressed(CursorAction::Command));
+
+        update(&mut app, long_press_time);
+        let actions = app.world.resource::<Input<CursorAction>>();
+        assert!(actions.just_pressed(CursorAction::Command));
+        assert!(!actions.pressed(CursorAction::Select));
+
+        // Lifting the finger afterwards doesn't also select.
+        touch(&mut app, 0, TouchPhase::Ended, Vec2::new(100., 100.));
+        update(&mut app, 0.05);
+        assert_ne!(gesture(&app), CursorGesture::Clicked);
+    }
+
+    #[test]
+    fn two_fingers_gesture() {
+        let mut app = touch_app();
+        touch(&mut app, 0, TouchPhase::Started, Vec2::new(100., 100.));
+        touch(&mut app, 1, TouchPhase::Started, Vec2::new(200., 100.));
+        update(&mut app, 0.05);
+        touch(&mut app, 0, TouchPhase::Moved, Vec2::new(110., 100.));
+        touch(&mut app, 1, TouchPhase::Moved, Vec2::new(210., 100.));
+        update(&mut app, 0.05);
+
+        let events = app.world.resource::<Events<TouchGesture>>();
+        let gestures: Vec<_> = events.get_reader().iter(events).copied().collect();
+        assert_eq!(gestures.len(), 1);
+        assert_eq!(gestures[0].touches, 2);
+        // Touches are flipped onto the cursor's bottom left origin.
+        assert_eq!(gestures[0].pan, Vec2::new(10., 0.));
+        assert_eq!(gestures[0].pinch, 1.);
+        assert_eq!(gestures[0].rotation, 0.);
+        assert_eq!(gesture(&app), CursorGesture::Idle);
+    }
+
+    fn gamepad_app() -> App {
+        let mut windows = Windows::default();
+        windows.add(Window::new(
+            WindowId::primary(),
+            &WindowDescriptor::default(),
+            1280,
+            720,
+            1.,
+            None,
+            None,
+        ));
+
+        let mut app = App::new();
+        app.add_plugin(CorePlugin::default())
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .insert_resource(Cursor {
+                settings: CursorPlugin::default(),
+                screen_location: Vec2::new(640., 360.),
+                ..default()
+            })
+            .insert_resource(Time::default())
+            .insert_resource(windows)
+            .init_resource::<Gamepads>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .add_event::<GamepadEvent>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_system_to_stage(CoreStage::PreUpdate, gamepad_connection_system)
+            .add_system_to_stage(CoreStage::PreUpdate, camera_system::<Projection>)
+            .add_system(gamepad_input_system);
+        app.world.send_event(GamepadEvent::new(
+            Gamepad::new(0),
+            GamepadEventType::Connected(GamepadInfo {
+                name: "Test".into(),
+            }),
+        ));
+        update(&mut app, 0.1);
+        app
+    }
+
+    fn push_stick(app: &mut App, stick: Vec2) {
+        let mut axes = app.world.resource_mut::<Axis<GamepadAxis>>();
+        let gamepad = Gamepad::new(0);
+        axes.set(
+            GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX),
+            stick.x,
+        );
+        axes.set(
+            GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY),
+            stick.y,
+        );
+    }
+
+    fn screen_location(app: &App) -> Vec2 {
+        app.world.resource::<Cursor>().screen_location
+    }
+
+    /// Moves the cursor for one frame of `seconds` and returns how far it went.
+    fn step(app: &mut App, seconds: f32) -> f32 {
+        let start = screen_location(app);
+        update(app, seconds);
+        screen_location(app).distance(start)
+    }
+
+    /// Spawns a camera looking at a `Pickable` and returns where it appears
+    /// on screen.
+    fn spawn_target(app: &mut App) -> Vec2 {
+        let camera_transform = Transform::from_xyz(0., 10., 10.).looking_at(Vec3::ZERO, Vec3::Y);
+        app.world.spawn((
+            Camera3dBundle {
+                transform: camera_transform,
+                global_transform: camera_transform.into(),
+                ..default()
+            },
+            RaycastSource::<RayReflector>::new(),
+        ));
+        app.world.spawn((GlobalTransform::IDENTITY, Pickable));
+        update(app, 0.1);
+
+        let (camera, camera_global) = app
+            .world
+            .query::<(&Camera, &GlobalTransform)>()
+            .single(&app.world);
+        camera.world_to_viewport(camera_global, Vec3::ZERO).unwrap()
+    }
+
+    #[test]
+    fn stick_accelerates() {
+        let mut app = gamepad_app();
+        let settings = app.world.resource::<Cursor>().settings.gamepad.clone();
+        push_stick(&mut app, Vec2::X);
+
+        let first = step(&mut app, 0.1);
+        let second = step(&mut app, 0.1);
+        let expected = settings.speed * (1. + settings.acceleration * 0.1) * 0.1;
+        assert!((first - expected).abs() < 1e-3, "{first} != {expected}");
+        assert!(second > first);
+
+        // Releasing the stick starts over from `speed`.
+        push_stick(&mut app, Vec2::ZERO);
+        update(&mut app, 0.1);
+        push_stick(&mut app, Vec2::NEG_X);
+        assert!((step(&mut app, 0.1) - first).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stick_speed_is_clamped() {
+        let mut app = gamepad_app();
+        let max_speed = app.world.resource::<Cursor>().settings.gamepad.max_speed;
+        push_stick(&mut app, Vec2::X);
+
+        let mut distances = Vec::new();
+        for _ in 0..30 {
+            // Keep the cursor clear of the window edge.
+            app.world.resource_mut::<Cursor>().screen_location = Vec2::new(100., 360.);
+            distances.push(step(&mut app, 0.1));
+        }
+        assert!(distances
+            .iter()
+            .all(|distance| *distance <= max_speed * 0.1 + 1e-3));
+        assert!((distances.last().unwrap() - max_speed * 0.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn released_cursor_snaps_to_nearby_pickable() {
+        let mut app = gamepad_app();
+        let target = spawn_target(&mut app);
+        let snap_radius = app.world.resource::<Cursor>().settings.gamepad.snap_radius;
+
+        // Nudge the stick so the gamepad owns the cursor, then let go.
+        app.world.resource_mut::<Cursor>().screen_location =
+            target + Vec2::new(snap_radius / 2., 0.);
+        push_stick(&mut app, Vec2::X * 0.2);
+        update(&mut app, 0.01);
+        push_stick(&mut app, Vec2::ZERO);
+        let before = screen_location(&app).distance(target);
+        for _ in 0..20 {
+            update(&mut app, 0.05);
+        }
+        assert!(screen_location(&app).distance(target) < before / 100.);
+    }
+
+    #[test]
+    fn distant_pickables_and_mouse_moves_are_not_snapped() {
+        let mut app = gamepad_app();
+        let target = spawn_target(&mut app);
+        let snap_radius = app.world.resource::<Cursor>().settings.gamepad.snap_radius;
+
+        let outside = target + Vec2::new(snap_radius * 2., 0.);
+        app.world.resource_mut::<Cursor>().screen_location = outside;
+        push_stick(&mut app, Vec2::X * 0.2);
+        update(&mut app, 0.01);
+        push_stick(&mut app, Vec2::ZERO);
+        let released = screen_location(&app);
+        update(&mut app, 0.05);
+        assert_eq!(screen_location(&app), released);
+
+        // A mouse move inside the radius leaves the cursor to the mouse.
+        let inside = target + Vec2::new(snap_radius / 2., 0.);
+        app.world.resource_mut::<Cursor>().screen_location = inside;
+        update(&mut app, 0.05);
+        update(&mut app, 0.05);
+        assert_eq!(screen_location(&app), inside);
+    }
+}
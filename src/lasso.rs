@@ -0,0 +1,95 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+
+use crate::{
+    util::OUTLINE_HEIGHT,
+    waypoints::{segment_transform, WaypointAssets},
+    Cursor, CursorGesture, SelectionShape,
+};
+
+/// Ground distance between consecutive points sampled along a lasso.
+pub const LASSO_SAMPLE_SPACING: f32 = 0.2;
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct LassoOutline;
+
+/// The outline segments spawned for the lasso being dragged.
+#[derive(Default)]
+pub struct DrawnLasso {
+    /// One segment per pair of consecutive sampled points.
+    sampled: Vec<Entity>,
+    /// From the last sampled point to the cursor, and back to the start.
+    closing: Option<[Entity; 2]>,
+}
+
+/// Draws the lasso being dragged as a closed loop on the ground, adding a
+/// segment per sampled point and moving only the closing segments each frame.
+pub fn lasso_outline_system(
+    mut commands: Commands,
+    cursor: Res<Cursor>,
+    waypoint_assets: Res<WaypointAssets>,
+    mut outlines: Query<&mut Transform, With<LassoOutline>>,
+    mut drawn: Local<DrawnLasso>,
+) {
+    let drawing = cursor.settings.selection_shape == SelectionShape::Lasso
+        && cursor.gesture == CursorGesture::Dragging
+        && !cursor.lasso.is_empty();
+    // A new lasso never has fewer points than the one drawn, unless it was
+    // restarted.
+    if !drawing || drawn.sampled.len() >= cursor.lasso.len() {
+        let closing = drawn.closing.take().into_iter().flatten();
+        for entity in drawn.sampled.drain(..).chain(closing) {
+            if let Some(entity_commands) = commands.get_entity(entity) {
+                entity_commands.despawn_recursive();
+            }
+        }
+    }
+    if !drawing {
+        return;
+    }
+
+    let thickness = cursor.settings.aesthetics.line_thickness / 2.;
+    let raise = Vec3::Y * OUTLINE_HEIGHT;
+    let mut spawn_segment = |from: Vec3, to: Vec3| {
+        commands
+            .spawn((
+                PbrBundle {
+                    mesh: waypoint_assets.line_mesh.clone(),
+                    material: waypoint_assets.ghost_material.clone(),
+                    transform: segment_transform(from + raise, to + raise, thickness),
+                    ..default()
+                },
+                NotShadowReceiver,
+                NotShadowCaster,
+                LassoOutline,
+            ))
+            .id()
+    };
+
+    for index in drawn.sampled.len()..cursor.lasso.len() - 1 {
+        let segment = spawn_segment(cursor.lasso[index], cursor.lasso[index + 1]);
+        drawn.sampled.push(segment);
+    }
+
+    // Close the loop from the cursor back to the start.
+    let (start, last) = (cursor.lasso[0], cursor.lasso[cursor.lasso.len() - 1]);
+    match drawn.closing {
+        Some([to_cursor, to_start]) => {
+            if let Ok(mut transform) = outlines.get_mut(to_cursor) {
+                *transform = segment_transform(last + raise, cursor.location + raise, thickness);
+            }
+            if let Ok(mut transform) = outlines.get_mut(to_start) {
+                *transform = segment_transform(cursor.location + raise, start + raise, thickness);
+            }
+        }
+        None => {
+            drawn.closing = Some([
+                spawn_segment(last, cursor.location),
+                spawn_segment(cursor.location, start),
+            ]);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use bevy::input::InputSystem;
 use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
 use bevy::prelude::*;
 use bevy::render::primitives::Aabb;
@@ -8,20 +9,74 @@ use bevy_mod_raycast::{
     RaycastSystem,
 };
 
+mod bindings;
+mod brush;
+mod cleanup;
+mod command;
 mod components;
 mod confirm_box;
+mod context;
 mod effects;
+mod events;
+mod formation;
+mod groups;
+mod icons;
+mod input;
+mod lasso;
+mod ownership;
+mod picking;
 mod resources;
 mod util;
+mod waypoints;
+mod world_cursor;
 
 pub use bevy_mod_raycast::RaycastSource;
-pub use components::{CursorReflector, Selected, SelectionHighlighter};
-pub use resources::{Aesthetics, Bounds2D, CursorPlugin};
+pub use bindings::{BoundInput, Chord, CursorBindings, InputButton};
+pub use brush::BrushOutline;
+pub use command::CursorCommand;
+pub use components::{
+    CursorReflector, HoverHighlighter, Hovered, Owner, PreviewHighlighter, Previewed, Selected,
+    SelectionHighlighter, SelectionKind, SelectionPriority,
+};
+pub use context::CursorContext;
+pub use events::{
+    EntityDeselected, EntitySelected, SelectionCancelled, SelectionCause, SelectionChanged,
+    SelectionEvents,
+};
+pub use formation::{FormationCommand, FormationGhost, FormationTarget};
+pub use groups::{ControlGroups, FocusControlGroup};
+pub use icons::CursorIconRules;
+pub use input::{CursorAction, TouchGesture};
+pub use lasso::LassoOutline;
+pub use ownership::{Allegiance, Allegiances};
+pub use resources::{
+    Aesthetics, Bounds2D, CommandSettings, ControlGroupSettings, CursorGesture, CursorPlugin,
+    GamepadCursorSettings, LocalPlayer, OwnershipSettings, SelectionDelta, SelectionMode,
+    SelectionOverflow, SelectionShape, SelectionSpace, TouchSettings,
+};
 
+use bindings::binding_input_system;
+use brush::brush_system;
+use cleanup::{prune_despawned_system, TryAddChild, TryInsert};
+use command::command_system;
 use confirm_box::create_selection_confirmation_outline;
+use context::cursor_context_system;
 use effects::blink_system;
+use formation::formation_preview_system;
+use groups::control_group_system;
+use icons::cursor_icon_system;
+use input::{clear_cursor_actions, gamepad_input_system, mouse_input_system, touch_input_system};
+use lasso::{lasso_outline_system, LASSO_SAMPLE_SPACING};
+use picking::{add_pick_source, hover_system, make_pickables_raycastable};
 pub use resources::Cursor;
-use util::{hypotenuse, is_position_in_area, keep_in_bounds};
+use util::{
+    hypotenuse, is_on_screen, is_point_in_polygon, is_point_in_rect, is_position_in_area,
+    keep_in_bounds, polygon_centroid, project_aabb, rects_overlap,
+};
+use waypoints::{setup_waypoint_assets, waypoint_command_system, waypoint_path_system};
+pub use waypoints::{WaypointPath, WaypointQueue};
+use world_cursor::world_cursor_system;
+pub use world_cursor::WorldCursor;
 
 impl Default for CursorPlugin {
     fn default() -> Self {
@@ -35,6 +90,22 @@ impl Default for CursorPlugin {
             y_inclusion_limit: 1.,
             torus_offset: 0.1,
             aesthetics: Default::default(),
+            debug_cursor: false,
+            touch: Default::default(),
+            gamepad: Default::default(),
+            ownership: Default::default(),
+            click_threshold: 0.25,
+            drag_threshold: 4.,
+            long_press_time: 0.5,
+            keep_selection_on_empty_box: true,
+            max_selected: None,
+            overflow: SelectionOverflow::BoxCenter,
+            double_click_time: 0.3,
+            selection_space: SelectionSpace::World,
+            selection_shape: SelectionShape::Box,
+            brush_radius: 1.,
+            control_groups: Default::default(),
+            command: Default::default(),
         }
     }
 }
@@ -43,18 +114,91 @@ impl Plugin for CursorPlugin {
     fn build(&self, app: &mut App) {
         let app = app
             .insert_resource(Cursor {
+                in_bounds: true,
                 settings: self.clone(),
                 ..Default::default()
             })
+            .add_event::<SelectionChanged>()
+            .add_event::<EntitySelected>()
+            .add_event::<EntityDeselected>()
+            .add_event::<SelectionCancelled>()
+            .add_event::<FocusControlGroup>()
+            .add_event::<CursorCommand>()
+            .add_event::<FormationCommand>()
+            .add_event::<TouchGesture>()
+            .init_resource::<Input<CursorAction>>()
+            .init_resource::<CursorBindings>()
+            .init_resource::<ControlGroups>()
+            .init_resource::<CursorIconRules>()
+            .init_resource::<LocalPlayer>()
             .add_plugin(DefaultRaycastingPlugin::<RayReflector>::default())
+            .add_plugin(DefaultRaycastingPlugin::<PickReflector>::default())
             .add_startup_system(setup)
-            .add_system(selection_system)
+            .add_startup_system(setup_waypoint_assets)
+            .add_system(add_pick_source)
+            .add_system(selection_system.after(mouse_system))
+            .add_system(hover_system)
+            .add_system(
+                click_selection_system
+                    .after(hover_system)
+                    .after(mouse_system),
+            )
+            .add_system(control_group_system)
+            .add_system(
+                highlight_system
+                    .after(mouse_system)
+                    .after(selection_system)
+                    .after(click_selection_system)
+                    .after(control_group_system),
+            )
             .add_system(mouse_system)
+            .add_system(brush_system.after(mouse_system))
+            .add_system(selection_preview_system.after(brush_system))
+            .add_system(lasso_outline_system.after(mouse_system))
+            .add_system(command_system.after(hover_system))
+            .add_system(waypoint_command_system.after(command_system))
+            .add_system(waypoint_path_system.after(waypoint_command_system))
+            .add_system(formation_preview_system.after(command_system))
+            .add_system(
+                cursor_context_system
+                    .after(hover_system)
+                    .after(mouse_system),
+            )
+            .add_system(cursor_icon_system.after(cursor_context_system))
+            .add_system(world_cursor_system.after(cursor_context_system))
+            .add_system_to_stage(CoreStage::First, clear_cursor_actions)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                binding_input_system.after(InputSystem),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, prune_despawned_system)
+            .add_system_to_stage(
+                CoreStage::First,
+                mouse_input_system.after(clear_cursor_actions),
+            )
+            .add_system_to_stage(
+                CoreStage::First,
+                touch_input_system
+                    .after(clear_cursor_actions)
+                    .after(mouse_input_system),
+            )
+            .add_system_to_stage(
+                CoreStage::First,
+                gamepad_input_system
+                    .after(clear_cursor_actions)
+                    .after(touch_input_system),
+            )
             .add_system_to_stage(
                 CoreStage::First,
-                update_raycast_with_cursor.before(RaycastSystem::BuildRays::<RayReflector>),
+                update_raycast_with_cursor
+                    .after(mouse_input_system)
+                    .after(touch_input_system)
+                    .after(gamepad_input_system)
+                    .before(RaycastSystem::BuildRays::<RayReflector>)
+                    .before(RaycastSystem::BuildRays::<PickReflector>),
             )
-            .add_system_to_stage(CoreStage::PostUpdate, make_scene_pickable);
+            .add_system_to_stage(CoreStage::PostUpdate, make_scene_pickable)
+            .add_system_to_stage(CoreStage::PostUpdate, make_pickables_raycastable);
 
         app.add_system(blink_system);
     }
@@ -74,6 +218,10 @@ pub struct Location {
 #[reflect(Component)]
 pub struct BoundingBox;
 
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct ScreenBoundingBox;
+
 #[derive(Component, Reflect, Default, Debug)]
 #[reflect(Component)]
 pub struct BoundingBoxArea {
@@ -89,11 +237,16 @@ pub struct BoundingBoxArea {
 #[reflect(Component)]
 pub struct RayReflector;
 
-fn setup(mut commands: Commands) {
-    // Overwrite the default plugin state with one that enables the debug cursor. This line can be
-    // removed if the debug cursor isn't needed as the state is set to default values when the
-    // default plugin is added.
-    commands.insert_resource(DefaultPluginState::<RayReflector>::default().with_debug_cursor());
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct PickReflector;
+
+fn setup(mut commands: Commands, cursor: Res<Cursor>) {
+    // Overwrite the default plugin state with one that enables the debug cursor. The state is set
+    // to default values, without the debug cursor, when the default plugin is added.
+    if cursor.settings.debug_cursor {
+        commands.insert_resource(DefaultPluginState::<RayReflector>::default().with_debug_cursor());
+    }
 }
 
 #[allow(clippy::type_complexity)]
@@ -105,170 +258,789 @@ fn make_scene_pickable(mut commands: Commands, mesh_query: Query<Entity, With<Cu
     }
 }
 
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn selection_system(
     mut commands: Commands,
     mut cursor: ResMut<Cursor>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    transforms: Query<(&Transform, &Aabb)>,
-    mut query: Query<Entity, With<Pickable>>,
+    query: Query<(Entity, &GlobalTransform, &Aabb), With<Pickable>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+    transforms: Query<&GlobalTransform>,
+    allegiances: Allegiances,
+    priorities: Query<&SelectionPriority>,
+    input: BoundInput,
+    mut events: SelectionEvents,
 ) {
     if cursor.selection.just_selected {
-        create_selection_confirmation_outline(
-            &mut commands,
+        if cursor.settings.selection_space == SelectionSpace::World
+            && cursor.settings.selection_shape == SelectionShape::Box
+        {
+            create_selection_confirmation_outline(
+                &mut commands,
+                &cursor,
+                &cursor.settings.aesthetics,
+                &mut meshes,
+                &mut materials,
+            );
+        }
+
+        let in_area = entities_in_selection(
             &cursor,
-            &cursor.settings.aesthetics,
-            &mut meshes,
-            &mut materials,
+            (cursor.xyz1, cursor.xyz2),
+            (cursor.screen_xy1, cursor.screen_xy2),
+            &query,
+            &cameras,
         );
 
-        for entity in query.iter_mut() {
-            match transforms.get(entity) {
-                Ok((transform, aabb)) => {
-                    // Create a tolerance vector for checking if positions
-                    // are in the area.
-                    let tolerance = Vec3::new(0., cursor.settings.y_inclusion_limit, 0.);
-
-                    let torus_size = hypotenuse(aabb.half_extents.x, aabb.half_extents.z)
-                        + cursor.settings.torus_offset;
-
-                    // Check if entities are within the highlighted area.
-                    if is_position_in_area(
-                        transform.translation,
-                        cursor.xyz1,
-                        cursor.xyz2,
-                        tolerance,
-                    ) {
-                        let relative_bottom_of_mesh = aabb.half_extents.y * -1.;
-                        let child_id = commands
-                            .spawn(PbrBundle {
-                                mesh: meshes.add(Mesh::from(shape::Torus {
-                                    ring_radius: cursor.settings.aesthetics.selected_line_thickness,
-                                    radius: torus_size,
-                                    ..default()
-                                })),
-                                material: materials.add(StandardMaterial {
-                                    base_color: cursor.settings.aesthetics.selected_area_box_color,
-                                    emissive: cursor.settings.aesthetics.selected_area_box_color,
-                                    ..default()
-                                }),
-                                transform: Transform {
-                                    translation: Vec3::new(0., relative_bottom_of_mesh, 0.),
-                                    ..default()
-                                },
-                                ..default()
-                            })
-                            .insert(SelectionHighlighter)
-                            .insert(Name::new("SelectionHighlighter"))
-                            .id();
-
-                        commands.entity(entity).add_child(child_id);
-
-                        // Track selected.
-                        cursor.selection.selected_units.insert(entity);
-                        commands.entity(entity).insert(Selected);
-                    }
+        let in_area = filter_box_candidates(&cursor, in_area, &allegiances, &priorities, &input);
+        // Only a replacing box that caught nothing can empty the selection.
+        let cause = if in_area.is_empty() {
+            SelectionCause::Clear
+        } else {
+            SelectionCause::Box
+        };
+        let center = selection_center(&cursor, (cursor.screen_xy1, cursor.screen_xy2), &cameras);
+        let resolved = resolve_box_selection(
+            &cursor,
+            in_area,
+            center,
+            &allegiances,
+            &transforms,
+            &cameras,
+            &priorities,
+        );
+        let delta = cursor.selection.apply(SelectionMode::Replace, resolved);
+        events.send(delta, cause);
+    }
+
+    cursor.selection.just_selected = false;
+}
+
+/// The `Pickable`s inside the world-space box `area` or, for the screen
+/// selection spaces, the screen rectangle `rect`.
+#[allow(clippy::type_complexity)]
+fn entities_in_selection(
+    cursor: &Cursor,
+    area: (Vec3, Vec3),
+    rect: (Vec2, Vec2),
+    query: &Query<(Entity, &GlobalTransform, &Aabb), With<Pickable>>,
+    cameras: &Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+) -> Vec<Entity> {
+    if cursor.settings.selection_shape == SelectionShape::Brush {
+        return query
+            .iter()
+            .filter(|(entity, _, _)| cursor.brushed.contains(entity))
+            .map(|(entity, _, _)| entity)
+            .collect();
+    }
+
+    if cursor.settings.selection_shape == SelectionShape::Lasso {
+        // The lasso is traced on the ground, so test entities from above,
+        // within `y_inclusion_limit` of the ground it was traced over.
+        let polygon: Vec<Vec2> = cursor
+            .lasso
+            .iter()
+            .map(|point| Vec2::new(point.x, point.z))
+            .collect();
+        let limit = cursor.settings.y_inclusion_limit;
+        let (low, high) = cursor
+            .lasso
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(low, high), point| {
+                (low.min(point.y), high.max(point.y))
+            });
+        return query
+            .iter()
+            .filter(|(_, transform, _)| {
+                let position = transform.translation();
+                position.y >= low - limit
+                    && position.y <= high + limit
+                    && is_point_in_polygon(Vec2::new(position.x, position.z), &polygon)
+            })
+            .map(|(entity, _, _)| entity)
+            .collect();
+    }
+
+    match cursor.settings.selection_space {
+        SelectionSpace::World => {
+            // Create a tolerance vector for checking if positions
+            // are in the area.
+            let tolerance = Vec3::new(0., cursor.settings.y_inclusion_limit, 0.);
+
+            // Check if entities are within the highlighted area.
+            query
+                .iter()
+                .filter(|(_, transform, _)| {
+                    is_position_in_area(transform.translation(), area.0, area.1, tolerance)
+                })
+                .map(|(entity, _, _)| entity)
+                .collect()
+        }
+        SelectionSpace::ScreenPosition | SelectionSpace::ScreenAabb => {
+            // Project entities through the camera and check them
+            // against the rectangle dragged on screen.
+            query
+                .iter()
+                .filter(|(_, transform, aabb)| {
+                    cameras.iter().any(|(camera, camera_transform)| {
+                        if cursor.settings.selection_space == SelectionSpace::ScreenAabb {
+                            project_aabb(camera, camera_transform, transform, aabb)
+                                .is_some_and(|projected| rects_overlap(projected, rect))
+                        } else {
+                            camera
+                                .world_to_viewport(camera_transform, transform.translation())
+                                .is_some_and(|point| is_point_in_rect(point, rect))
+                        }
+                    })
+                })
+                .map(|(entity, _, _)| entity)
+                .collect()
+        }
+    }
+}
+
+/// The screen point `SelectionOverflow::BoxCenter` measures from: the middle
+/// of `rect`, or where the centroid of a lasso appears on screen.
+#[allow(clippy::type_complexity)]
+fn selection_center(
+    cursor: &Cursor,
+    rect: (Vec2, Vec2),
+    cameras: &Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+) -> Vec2 {
+    let rect_center = (rect.0 + rect.1) / 2.;
+    if cursor.settings.selection_shape != SelectionShape::Lasso {
+        return rect_center;
+    }
+
+    let polygon: Vec<Vec2> = cursor
+        .lasso
+        .iter()
+        .map(|point| Vec2::new(point.x, point.z))
+        .collect();
+    let Some(centroid) = polygon_centroid(&polygon) else {
+        return rect_center;
+    };
+    let height = cursor.lasso.iter().map(|point| point.y).sum::<f32>() / cursor.lasso.len() as f32;
+    cameras
+        .iter()
+        .find_map(|(camera, camera_transform)| {
+            camera.world_to_viewport(camera_transform, Vec3::new(centroid.x, height, centroid.y))
+        })
+        .unwrap_or(rect_center)
+}
+
+/// Narrows the entities caught by a box to the most preferred `Allegiance`,
+/// e.g. own units over neutral props, and then to the highest
+/// `SelectionPriority` unless the override modifier is held.
+fn filter_box_candidates(
+    cursor: &Cursor,
+    in_area: Vec<Entity>,
+    allegiances: &Allegiances,
+    priorities: &Query<&SelectionPriority>,
+    input: &BoundInput,
+) -> Vec<Entity> {
+    let in_area = allegiances.prioritize(in_area, &cursor.settings.ownership.priority);
+    if input.any_pressed(&input.bindings.priority_override) {
+        return in_area;
+    }
+
+    let priority_of = |entity: Entity| priorities.get(entity).copied().unwrap_or_default();
+    let Some(highest) = in_area.iter().map(|&entity| priority_of(entity)).max() else {
+        return in_area;
+    };
+    in_area
+        .into_iter()
+        .filter(|&entity| priority_of(entity) == highest)
+        .collect()
+}
+
+/// The selection releasing a box over `in_area` would leave, resolved
+/// against the current selection and trimmed by `limit_selection`. The
+/// previous selection stays when the box caught nothing, if so configured.
+#[allow(clippy::type_complexity)]
+fn resolve_box_selection(
+    cursor: &Cursor,
+    in_area: Vec<Entity>,
+    center: Vec2,
+    allegiances: &Allegiances,
+    transforms: &Query<&GlobalTransform>,
+    cameras: &Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+    priorities: &Query<&SelectionPriority>,
+) -> Vec<Entity> {
+    if in_area.is_empty() && cursor.settings.keep_selection_on_empty_box {
+        return cursor.selection.selected_units.iter().copied().collect();
+    }
+    let priority = &cursor.settings.ownership.priority;
+    let resolved = allegiances.resolve(&cursor.selection, cursor.selection.mode, in_area, priority);
+    limit_selection(cursor, resolved, center, transforms, cameras, priorities)
+}
+
+/// Trims `entities` to `CursorPlugin::max_selected`, keeping those first in
+/// the `CursorPlugin::overflow` order. `center` is the screen point distances
+/// are measured from for `SelectionOverflow::BoxCenter`.
+#[allow(clippy::type_complexity)]
+fn limit_selection(
+    cursor: &Cursor,
+    mut entities: Vec<Entity>,
+    center: Vec2,
+    transforms: &Query<&GlobalTransform>,
+    cameras: &Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+    priorities: &Query<&SelectionPriority>,
+) -> Vec<Entity> {
+    let Some(max_selected) = cursor.settings.max_selected else {
+        return entities;
+    };
+    if entities.len() <= max_selected {
+        return entities;
+    }
+
+    // Sorting by entity first makes every tie resolve the same way.
+    entities.sort();
+    let position = |entity: Entity| {
+        transforms
+            .get(entity)
+            .map_or(Vec3::splat(f32::MAX), |transform| transform.translation())
+    };
+    match cursor.settings.overflow {
+        SelectionOverflow::BoxCenter => {
+            let distance = |entity: Entity| {
+                cameras
+                    .iter()
+                    .find_map(|(camera, camera_transform)| {
+                        camera.world_to_viewport(camera_transform, position(entity))
+                    })
+                    .map_or(f32::MAX, |point| point.distance(center))
+            };
+            entities.sort_by(|a, b| distance(*a).total_cmp(&distance(*b)));
+        }
+        SelectionOverflow::Camera => {
+            let eye = cameras
+                .iter()
+                .next()
+                .map_or(Vec3::ZERO, |(_, camera_transform)| {
+                    camera_transform.translation()
+                });
+            let distance = |entity: Entity| position(entity).distance(eye);
+            entities.sort_by(|a, b| distance(*a).total_cmp(&distance(*b)));
+        }
+        SelectionOverflow::Priority => {
+            entities.sort_by_key(|&entity| {
+                std::cmp::Reverse(priorities.get(entity).copied().unwrap_or_default())
+            });
+        }
+        SelectionOverflow::InsertionOrder => {
+            let order = &cursor.selection.order;
+            entities.sort_by_key(|entity| {
+                order
+                    .iter()
+                    .position(|other| other == entity)
+                    .unwrap_or(usize::MAX)
+            });
+        }
+    }
+    entities.truncate(max_selected);
+    entities
+}
+
+/// Marks the entities releasing the box being dragged would leave selected as
+/// `Previewed`, with an optional ring, so players can see what it will do.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn selection_preview_system(
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &GlobalTransform, &Aabb), With<Pickable>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+    boxes: Query<&Transform, With<BoundingBox>>,
+    preview_highlights: Query<(Entity, &Parent), With<PreviewHighlighter>>,
+    transforms: Query<&GlobalTransform>,
+    allegiances: Allegiances,
+    priorities: Query<&SelectionPriority>,
+    input: BoundInput,
+) {
+    let preview: HashSet<Entity> = if cursor.gesture == CursorGesture::Dragging {
+        let area = boxes
+            .get_single()
+            .map(|transform| get_rectangle_points(transform.translation, transform.scale))
+            .unwrap_or((cursor.pressed_location, cursor.pressed_location));
+        let rect = (
+            cursor.pressed_screen_location.min(cursor.screen_location),
+            cursor.pressed_screen_location.max(cursor.screen_location),
+        );
+        let in_area = entities_in_selection(&cursor, area, rect, &query, &cameras);
+        let in_area = filter_box_candidates(&cursor, in_area, &allegiances, &priorities, &input);
+        let center = selection_center(&cursor, rect, &cameras);
+        resolve_box_selection(
+            &cursor,
+            in_area,
+            center,
+            &allegiances,
+            &transforms,
+            &cameras,
+            &priorities,
+        )
+        .into_iter()
+        .collect()
+    } else {
+        HashSet::new()
+    };
+    if preview == cursor.selection.preview {
+        return;
+    }
+
+    for &entity in cursor.selection.preview.difference(&preview) {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<Previewed>();
+        }
+        for (highlight, parent) in &preview_highlights {
+            if parent.get() == entity {
+                commands.entity(highlight).despawn_recursive();
+            }
+        }
+    }
+
+    let aesthetics = &cursor.settings.aesthetics;
+    for &entity in preview.difference(&cursor.selection.preview) {
+        commands.add(TryInsert {
+            entity,
+            bundle: Previewed,
+        });
+
+        if let (true, Ok((_, _, aabb))) = (aesthetics.show_preview_ring, query.get(entity)) {
+            spawn_ring(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                entity,
+                aabb,
+                cursor.settings.torus_offset,
+                aesthetics.selected_line_thickness / 2.,
+                aesthetics.preview_color,
+                AlphaMode::Blend,
+                (PreviewHighlighter, Name::new("PreviewHighlighter")),
+            );
+        }
+    }
+
+    cursor.selection.preview = preview;
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn click_selection_system(
+    mut cursor: ResMut<Cursor>,
+    input: BoundInput,
+    time: Res<Time>,
+    kinds: Query<(Entity, &GlobalTransform, Option<&SelectionKind>), With<Pickable>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+    transforms: Query<&GlobalTransform>,
+    priorities: Query<&SelectionPriority>,
+    allegiances: Allegiances,
+    mut events: SelectionEvents,
+) {
+    if !cursor.selection.just_clicked {
+        return;
+    }
+    cursor.selection.just_clicked = false;
+
+    let picked = cursor.hovered;
+
+    // A second click on the same entity in time, or a click with the
+    // same-kind modifier, selects everything on screen of its kind.
+    let now = time.elapsed_seconds_f64();
+    let double_clicked = match (picked, cursor.selection.last_click) {
+        (Some(entity), Some((last_entity, last_time))) => {
+            entity == last_entity && now - last_time <= cursor.settings.double_click_time as f64
+        }
+        _ => false,
+    };
+    cursor.selection.last_click = if double_clicked {
+        None
+    } else {
+        picked.map(|entity| (entity, now))
+    };
+    let same_kind = double_clicked || input.any_pressed(&input.bindings.same_kind);
+
+    // Clicking empty ground drops the selection.
+    if picked.is_none() && cursor.selection.mode == SelectionMode::Replace {
+        let delta = cursor.selection.clear();
+        events.send(delta, SelectionCause::Clear);
+        return;
+    }
+
+    let settings = cursor.settings.ownership.clone();
+    let kind = picked
+        .and_then(|entity| kinds.get(entity).ok())
+        .and_then(|(_, _, kind)| kind.copied());
+    let resolved = match picked {
+        // Entities that can't be selected alongside others, such as enemies,
+        // are inspected on their own by a plain click. Modified clicks on
+        // them leave the selection be.
+        Some(entity) if !settings.priority.contains(&allegiances.of(entity)) => {
+            if !settings.click_inspect || cursor.selection.mode != SelectionMode::Replace {
+                return;
+            }
+            vec![entity]
+        }
+        Some(entity) if same_kind && kind.is_some() => {
+            let entities: Vec<Entity> = kinds
+                .iter()
+                .filter(|(_, _, other)| other.copied() == kind)
+                .filter(|(other, _, _)| allegiances.of(*other) == allegiances.of(entity))
+                .filter(|(_, transform, _)| {
+                    cameras.iter().any(|(camera, camera_transform)| {
+                        is_on_screen(camera, camera_transform, transform.translation())
+                    })
+                })
+                .map(|(entity, _, _)| entity)
+                .collect();
+            let mode = match cursor.selection.mode {
+                SelectionMode::Add => SelectionMode::Add,
+                _ => SelectionMode::Replace,
+            };
+            allegiances.resolve(&cursor.selection, mode, entities, &settings.priority)
+        }
+        // Without a kind to match, the same-kind modifier falls back to its
+        // plain meaning, e.g. Ctrl toggling the clicked entity.
+        _ => allegiances.resolve(
+            &cursor.selection,
+            cursor.selection.mode,
+            picked,
+            &settings.priority,
+        ),
+    };
+    let center = cursor.screen_location;
+    let resolved = limit_selection(
+        &cursor,
+        resolved,
+        center,
+        &transforms,
+        &cameras,
+        &priorities,
+    );
+    let delta = cursor.selection.apply(SelectionMode::Replace, resolved);
+    events.send(delta, SelectionCause::Click);
+}
+
+/// Keeps `Selected` markers and their `SelectionHighlighter` rings in step
+/// with `Cursor::selection`.
+fn highlight_system(
+    mut commands: Commands,
+    cursor: Res<Cursor>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    extents: Query<&Aabb>,
+    selected: Query<Entity, With<Selected>>,
+    selection_highlights: Query<(Entity, &Parent), With<SelectionHighlighter>>,
+) {
+    let selected_units = &cursor.selection.selected_units;
+
+    // Clear Selected and SelectionHighlights on deselect.
+    for entity in &selected {
+        if !selected_units.contains(&entity) {
+            commands.entity(entity).remove::<Selected>();
+            for (highlight, parent) in &selection_highlights {
+                if parent.get() == entity {
+                    commands.entity(highlight).despawn_recursive();
                 }
-                Err(_) => println!("Failed to get extents."),
             }
         }
     }
 
-    cursor.selection.just_selected = false;
+    for &entity in selected_units {
+        if selected.contains(entity) {
+            continue;
+        }
+
+        commands.add(TryInsert {
+            entity,
+            bundle: Selected,
+        });
+
+        match extents.get(entity) {
+            Ok(aabb) => spawn_ring(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                entity,
+                aabb,
+                cursor.settings.torus_offset,
+                cursor.settings.aesthetics.selected_line_thickness,
+                cursor.settings.aesthetics.selected_area_box_color,
+                AlphaMode::Opaque,
+                (SelectionHighlighter, Name::new("SelectionHighlighter")),
+            ),
+            Err(_) => println!("Failed to get extents."),
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn mouse_system(
     mut commands: Commands,
     mut cursor: ResMut<Cursor>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut query: Query<(&mut Transform, &BoundingBox)>,
-    buttons: Res<Input<MouseButton>>,
+    mut screen_query: Query<&mut Style, With<ScreenBoundingBox>>,
+    actions: Res<Input<CursorAction>>,
+    input: BoundInput,
+    time: Res<Time>,
     intersection_query: Query<&Intersection<RayReflector>>,
-    selection_highlights: Query<Entity, With<SelectionHighlighter>>,
-    selected: Query<Entity, With<Selected>>,
+    sources: Query<&RaycastSource<RayReflector>>,
+    mut events: SelectionEvents,
 ) {
     // RayCast to get the mouse position in game coordinates.
+    let mut hit_ground = false;
     for intersection in &intersection_query {
         if let Some(xyz) = intersection.position() {
-            cursor.location = xyz.to_owned();
-            cursor.location = keep_in_bounds(&cursor.settings.bounds, cursor.location, 0.);
+            cursor.location = keep_in_bounds(&cursor.settings.bounds, *xyz, 0.);
+            cursor.in_bounds = cursor.location == *xyz;
+            cursor.normal = intersection.normal().unwrap_or(Vec3::Y);
+            hit_ground = true;
         }
     }
+    // A ray that misses every `CursorReflector` points off the ground.
+    if !hit_ground && sources.iter().any(|source| source.get_ray().is_some()) {
+        cursor.in_bounds = false;
+    }
+
+    let now = time.elapsed_seconds_f64();
 
-    if buttons.just_pressed(MouseButton::Left) {
+    // Clicks, drags and cancels are only reported for the frame they end.
+    if matches!(
+        cursor.gesture,
+        CursorGesture::Clicked | CursorGesture::Dragged | CursorGesture::Cancelled
+    ) {
+        cursor.gesture = CursorGesture::Idle;
+    }
+
+    if actions.just_pressed(CursorAction::Select) {
         cursor.pressed_location = cursor.location;
+        cursor.pressed_screen_location = cursor.screen_location;
+        cursor.pressed_time = now;
+        cursor.gesture = CursorGesture::Pressed;
+        cursor.selection.snapshot = cursor.selection.selected_units.clone();
 
         cursor.xyz1 = Vec3::new(-1., -1., -1.);
         cursor.xyz2 = Vec3::new(-1., -1., -1.);
-
-        cursor.selection.entity = Some(
-            commands
-                .spawn((
-                    PbrBundle {
-                        material: materials.add(StandardMaterial {
-                            alpha_mode: AlphaMode::Blend,
-                            base_color: cursor.settings.aesthetics.bounding_box_color,
-                            emissive: cursor.settings.aesthetics.bounding_box_color,
-                            unlit: false,
-                            ..default()
-                        }),
-                        transform: Transform {
-                            translation: Vec3::new(
-                                cursor.location.x,
-                                cursor.location.y - 0.1,
-                                cursor.location.z,
-                            ),
-                            ..Default::default()
-                        },
-                        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
-                        ..default()
-                    },
-                    BoundingBox,
-                ))
-                .insert(NotShadowReceiver)
-                .insert(NotShadowCaster)
-                .insert(Name::new("SelectionBox"))
-                .id(),
-        );
+        cursor.lasso.clear();
+        cursor.brushed.clear();
 
         // Handle selection action.
-        if !cursor.selection.selected_units.is_empty() {
-            cursor.selection.selected_units = HashSet::new();
+        cursor.selection.mode = input.selection_mode();
 
-            // Removed Selected
-            for entity in &selected {
-                commands.entity(entity).remove::<Selected>();
-            }
-
-            // Clear SelectionHighlights on deselect.
-            for entity in &selection_highlights {
-                commands.entity(entity).despawn_recursive();
-            }
+        // A brush paints from the press, so there is no click to tell apart.
+        if cursor.settings.selection_shape == SelectionShape::Brush {
+            cursor.gesture = CursorGesture::Dragging;
         }
     };
 
-    if buttons.just_released(MouseButton::Left) {
+    // Cancel or the command button abort a held select, dropping the box and
+    // restoring the selection from before the press.
+    if matches!(
+        cursor.gesture,
+        CursorGesture::Pressed | CursorGesture::LongPressed | CursorGesture::Dragging
+    ) && (actions.just_pressed(CursorAction::Cancel)
+        || actions.just_pressed(CursorAction::Command))
+    {
+        if let Some(entity) = cursor.selection.entity.take() {
+            commands.entity(entity).despawn_recursive();
+        }
+        cursor.lasso.clear();
+        cursor.brushed.clear();
+        cursor.gesture = CursorGesture::Cancelled;
         cursor.pressed_location = Vec3::new(-1., -1., -1.);
+
+        let snapshot = std::mem::take(&mut cursor.selection.snapshot);
+        let mut restored: Vec<Entity> = snapshot.iter().copied().collect();
+        restored.sort();
+        let delta = cursor.selection.apply(SelectionMode::Replace, snapshot);
+        events.send_cancelled(delta, restored);
     }
 
-    if let Ok((mut transform, _)) = query.get_single_mut() {
-        if buttons.pressed(MouseButton::Left) {
+    if actions.pressed(CursorAction::Select) {
+        // Only start a selection box once the cursor has moved far enough
+        // from the press, so jitter during a click doesn't become a box.
+        if matches!(
+            cursor.gesture,
+            CursorGesture::Pressed | CursorGesture::LongPressed
+        ) {
+            let drag = cursor.location - cursor.pressed_location;
+            let screen_drag = cursor.screen_location - cursor.pressed_screen_location;
+            if Vec2::new(drag.x, drag.z).length() >= cursor.settings.click_threshold
+                || screen_drag.length() >= cursor.settings.drag_threshold
+            {
+                cursor.gesture = CursorGesture::Dragging;
+                match cursor.settings.selection_shape {
+                    SelectionShape::Box => {
+                        cursor.selection.entity = Some(spawn_selection_box(
+                            &mut commands,
+                            &cursor,
+                            &mut meshes,
+                            &mut materials,
+                        ));
+                    }
+                    SelectionShape::Lasso => {
+                        let start = cursor.pressed_location;
+                        cursor.lasso.push(start);
+                    }
+                    // Brushes start dragging on the press.
+                    SelectionShape::Brush => {}
+                }
+            } else if now - cursor.pressed_time >= cursor.settings.long_press_time as f64 {
+                cursor.gesture = CursorGesture::LongPressed;
+            }
+        }
+
+        // Sample the lasso along the ground under the cursor.
+        if cursor.gesture == CursorGesture::Dragging
+            && cursor
+                .lasso
+                .last()
+                .is_some_and(|last| last.distance(cursor.location) >= LASSO_SAMPLE_SPACING)
+        {
+            let location = cursor.location;
+            cursor.lasso.push(location);
+        }
+
+        if let Ok((mut transform, _)) = query.get_single_mut() {
             let difference = cursor.location - cursor.pressed_location;
             transform.translation = cursor.pressed_location + difference / 2.;
             // Raise the selection box slightly or will clip with ground.
             transform.translation[1] += 0.1;
             transform.scale = Vec3::new(difference.x, 0.0, difference.z);
         }
-        if buttons.just_released(MouseButton::Left) {
-            if let Some(entity) = cursor.selection.entity {
-                cursor.selection.just_selected = true;
+        if let Ok(mut style) = screen_query.get_single_mut() {
+            let min = cursor.pressed_screen_location.min(cursor.screen_location);
+            let size = (cursor.pressed_screen_location - cursor.screen_location).abs();
+            style.position = UiRect {
+                left: Val::Px(min.x),
+                bottom: Val::Px(min.y),
+                ..default()
+            };
+            style.size = Size::new(Val::Px(size.x), Val::Px(size.y));
+        }
+    }
+
+    if actions.just_released(CursorAction::Select) && cursor.gesture != CursorGesture::Cancelled {
+        if cursor.gesture == CursorGesture::Dragging {
+            cursor.gesture = CursorGesture::Dragged;
+            cursor.selection.just_selected = true;
 
-                (cursor.xyz1, cursor.xyz2) =
-                    get_rectangle_points(transform.translation, transform.scale);
+            if let Some(entity) = cursor.selection.entity.take() {
+                if let Ok((transform, _)) = query.get(entity) {
+                    (cursor.xyz1, cursor.xyz2) =
+                        get_rectangle_points(transform.translation, transform.scale);
+                }
                 commands.entity(entity).despawn_recursive();
             }
+            if cursor.settings.selection_shape == SelectionShape::Lasso {
+                let location = cursor.location;
+                cursor.lasso.push(location);
+            }
+            cursor.screen_xy1 = cursor.pressed_screen_location.min(cursor.screen_location);
+            cursor.screen_xy2 = cursor.pressed_screen_location.max(cursor.screen_location);
+        } else if cursor.gesture != CursorGesture::Idle {
+            // Released before passing a drag threshold: select whatever is
+            // under the cursor rather than a zero-area box.
+            cursor.gesture = CursorGesture::Clicked;
+            cursor.selection.just_clicked = true;
         }
+
+        cursor.pressed_location = Vec3::new(-1., -1., -1.);
+    }
+}
+
+/// Spawns a torus of `color` as a child of `entity`, around the bottom of its
+/// `Aabb` with `torus_offset` to spare.
+#[allow(clippy::too_many_arguments)]
+fn spawn_ring(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    entity: Entity,
+    aabb: &Aabb,
+    torus_offset: f32,
+    ring_radius: f32,
+    color: Color,
+    alpha_mode: AlphaMode,
+    marker: impl Bundle,
+) {
+    let radius = hypotenuse(aabb.half_extents.x, aabb.half_extents.z) + torus_offset;
+    let child_id = commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Torus {
+                ring_radius,
+                radius,
+                ..default()
+            })),
+            material: materials.add(StandardMaterial {
+                alpha_mode,
+                base_color: color,
+                emissive: color,
+                ..default()
+            }),
+            transform: Transform::from_xyz(0., -aabb.half_extents.y, 0.),
+            ..default()
+        })
+        .insert(marker)
+        .id();
+    commands.add(TryAddChild {
+        parent: entity,
+        child: child_id,
+    });
+}
+
+fn spawn_selection_box(
+    commands: &mut Commands,
+    cursor: &Cursor,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) -> Entity {
+    match cursor.settings.selection_space {
+        SelectionSpace::World => commands
+            .spawn((
+                PbrBundle {
+                    material: materials.add(StandardMaterial {
+                        alpha_mode: AlphaMode::Blend,
+                        base_color: cursor.settings.aesthetics.bounding_box_color,
+                        emissive: cursor.settings.aesthetics.bounding_box_color,
+                        unlit: false,
+                        ..default()
+                    }),
+                    transform: Transform {
+                        translation: Vec3::new(
+                            cursor.pressed_location.x,
+                            cursor.pressed_location.y - 0.1,
+                            cursor.pressed_location.z,
+                        ),
+                        ..Default::default()
+                    },
+                    mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+                    ..default()
+                },
+                BoundingBox,
+            ))
+            .insert(NotShadowReceiver)
+            .insert(NotShadowCaster)
+            .insert(Name::new("SelectionBox"))
+            .id(),
+        SelectionSpace::ScreenPosition | SelectionSpace::ScreenAabb => commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    background_color: cursor.settings.aesthetics.bounding_box_color.into(),
+                    ..default()
+                },
+                ScreenBoundingBox,
+            ))
+            .insert(Name::new("SelectionBox"))
+            .id(),
     }
 }
 
@@ -294,15 +1066,268 @@ fn get_rectangle_points(position: Vec3, scale: Vec3) -> (Vec3, Vec3) {
 }
 
 fn update_raycast_with_cursor(
-    mut cursor: EventReader<CursorMoved>,
+    cursor: Res<Cursor>,
     mut query: Query<&mut RaycastSource<RayReflector>>,
+    mut pickable_query: Query<&mut RaycastSource<PickReflector>>,
 ) {
-    let cursor_position = match cursor.iter().last() {
-        Some(cursor_moved) => cursor_moved.position,
-        None => return,
-    };
+    let cursor_position = cursor.screen_location;
 
     for mut pick_source in &mut query {
         pick_source.cast_method = RaycastMethod::Screenspace(cursor_position);
     }
+
+    for mut pick_source in &mut pickable_query {
+        pick_source.cast_method = RaycastMethod::Screenspace(cursor_position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::AssetPlugin,
+        ecs::system::SystemState,
+        render::camera::camera_system,
+        window::{WindowCreated, WindowId, WindowResized},
+    };
+
+    use super::*;
+
+    type LimitParams<'w, 's> = (
+        Query<'w, 's, &'static GlobalTransform>,
+        Query<
+            'w,
+            's,
+            (&'static Camera, &'static GlobalTransform),
+            With<RaycastSource<RayReflector>>,
+        >,
+        Query<'w, 's, &'static SelectionPriority>,
+    );
+
+    fn cursor(max_selected: usize, overflow: SelectionOverflow) -> Cursor {
+        Cursor {
+            settings: CursorPlugin {
+                max_selected: Some(max_selected),
+                overflow,
+                ..default()
+            },
+            ..default()
+        }
+    }
+
+    /// A world holding a cursor camera at `camera_transform`, with its
+    /// viewport computed so entities can be projected onto the screen.
+    fn camera_world(camera_transform: Transform) -> App {
+        let mut windows = Windows::default();
+        windows.add(Window::new(
+            WindowId::primary(),
+            &WindowDescriptor::default(),
+            1280,
+            720,
+            1.,
+            None,
+            None,
+        ));
+
+        let mut app = App::new();
+        app.add_plugin(CorePlugin::default())
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .insert_resource(windows)
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_system(camera_system::<Projection>);
+        app.world.spawn((
+            Camera3dBundle {
+                transform: camera_transform,
+                global_transform: camera_transform.into(),
+                ..default()
+            },
+            RaycastSource::<RayReflector>::new(),
+        ));
+        app.update();
+        app
+    }
+
+    fn screen_position(world: &mut World, position: Vec3) -> Vec2 {
+        let (camera, camera_transform) = world.query::<(&Camera, &GlobalTransform)>().single(world);
+        camera
+            .world_to_viewport(camera_transform, position)
+            .unwrap()
+    }
+
+    fn limit(
+        world: &mut World,
+        cursor: &Cursor,
+        entities: Vec<Entity>,
+        center: Vec2,
+    ) -> Vec<Entity> {
+        let mut state: SystemState<LimitParams> = SystemState::new(world);
+        let (transforms, cameras, priorities) = state.get(world);
+        limit_selection(cursor, entities, center, &transforms, &cameras, &priorities)
+    }
+
+    fn spawn_units(world: &mut World, count: usize) -> Vec<Entity> {
+        (0..count)
+            .map(|_| {
+                world
+                    .spawn((GlobalTransform::IDENTITY, SelectionPriority(0)))
+                    .id()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ties_keep_the_same_entities_in_any_order() {
+        for overflow in [
+            SelectionOverflow::BoxCenter,
+            SelectionOverflow::Camera,
+            SelectionOverflow::Priority,
+            SelectionOverflow::InsertionOrder,
+        ] {
+            // Every unit sits at the same finite distance from the camera and
+            // from the box center.
+            let mut app =
+                camera_world(Transform::from_xyz(0., 10., 10.).looking_at(Vec3::ZERO, Vec3::Y));
+            let world = &mut app.world;
+            let units = spawn_units(world, 5);
+            let center = screen_position(world, Vec3::X);
+            let cursor = cursor(2, overflow);
+
+            let forward = limit(world, &cursor, units.clone(), center);
+            let backward = limit(
+                world,
+                &cursor,
+                units.iter().rev().copied().collect(),
+                center,
+            );
+            assert_eq!(forward, units[..2], "{overflow:?}");
+            assert_eq!(backward, forward, "{overflow:?}");
+        }
+    }
+
+    #[test]
+    fn priority_keeps_the_highest() {
+        let mut world = World::new();
+        let units = spawn_units(&mut world, 4);
+        for (unit, priority) in units.iter().zip([1, 3, 0, 2]) {
+            world.entity_mut(*unit).insert(SelectionPriority(priority));
+        }
+
+        let kept = limit(
+            &mut world,
+            &cursor(2, SelectionOverflow::Priority),
+            units.clone(),
+            Vec2::ZERO,
+        );
+        assert_eq!(kept, vec![units[1], units[3]]);
+    }
+
+    #[test]
+    fn camera_keeps_the_closest() {
+        let mut app =
+            camera_world(Transform::from_xyz(0., 10., 10.).looking_at(Vec3::ZERO, Vec3::Y));
+        let world = &mut app.world;
+        let units = spawn_units(world, 3);
+        for (unit, z) in units.iter().zip([5., 1., 3.]) {
+            world
+                .entity_mut(*unit)
+                .insert(GlobalTransform::from_xyz(0., 0., z));
+        }
+
+        // Units nearer the origin would win without the camera.
+        let kept = limit(
+            world,
+            &cursor(2, SelectionOverflow::Camera),
+            units.clone(),
+            Vec2::ZERO,
+        );
+        assert_eq!(kept, vec![units[0], units[2]]);
+    }
+
+    #[test]
+    fn box_center_keeps_the_closest_on_screen() {
+        let mut app =
+            camera_world(Transform::from_xyz(0., 10., 10.).looking_at(Vec3::ZERO, Vec3::Y));
+        let world = &mut app.world;
+        let units = spawn_units(world, 4);
+        for (unit, x) in units.iter().zip([-4., 3., 0.5, -1.5]) {
+            world
+                .entity_mut(*unit)
+                .insert(GlobalTransform::from_xyz(x, 0., 0.));
+        }
+        let center = screen_position(world, Vec3::new(1., 0., 0.));
+
+        let kept = limit(
+            world,
+            &cursor(2, SelectionOverflow::BoxCenter),
+            units.clone(),
+            center,
+        );
+        assert_eq!(kept, vec![units[2], units[1]]);
+    }
+
+    #[test]
+    fn insertion_order_keeps_the_earliest_joined() {
+        let mut world = World::new();
+        let units = spawn_units(&mut world, 5);
+        let mut cursor = cursor(3, SelectionOverflow::InsertionOrder);
+        cursor.selection.apply(SelectionMode::Add, [units[3]]);
+        cursor.selection.apply(SelectionMode::Add, [units[1]]);
+        assert_eq!(cursor.selection.order, vec![units[3], units[1]]);
+
+        let kept = limit(&mut world, &cursor, units.clone(), Vec2::ZERO);
+        assert_eq!(kept, vec![units[3], units[1], units[0]]);
+
+        // Leaving and rejoining the selection moves an entity to the back.
+        cursor.selection.apply(SelectionMode::Toggle, [units[3]]);
+        cursor.selection.apply(SelectionMode::Toggle, [units[3]]);
+        assert_eq!(cursor.selection.order, vec![units[1], units[3]]);
+
+        // Replacing keeps the order of entities that stay selected.
+        cursor
+            .selection
+            .apply(SelectionMode::Replace, [units[4], units[3], units[0]]);
+        assert_eq!(cursor.selection.order, vec![units[3], units[0], units[4]]);
+    }
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn lasso_respects_y_inclusion_limit() {
+        let mut world = World::new();
+        let aabb = Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5));
+        let [ground, low, _too_high] = [0.5, -0.9, 1.5].map(|y| {
+            world
+                .spawn((GlobalTransform::from_xyz(1., y, 1.), aabb.clone(), Pickable))
+                .id()
+        });
+        let mut cursor = Cursor {
+            settings: CursorPlugin {
+                selection_shape: SelectionShape::Lasso,
+                y_inclusion_limit: 1.,
+                ..default()
+            },
+            ..default()
+        };
+        cursor.lasso = vec![
+            Vec3::new(0., 0., 0.),
+            Vec3::new(2., 0., 0.),
+            Vec3::new(2., 0., 2.),
+            Vec3::new(0., 0., 2.),
+        ];
+
+        let mut state: SystemState<(
+            Query<(Entity, &GlobalTransform, &Aabb), With<Pickable>>,
+            Query<(&Camera, &GlobalTransform), With<RaycastSource<RayReflector>>>,
+        )> = SystemState::new(&mut world);
+        let (query, cameras) = state.get(&world);
+        let mut caught = entities_in_selection(
+            &cursor,
+            (Vec3::ZERO, Vec3::ZERO),
+            (Vec2::ZERO, Vec2::ZERO),
+            &query,
+            &cameras,
+        );
+        caught.sort();
+        assert_eq!(caught, vec![ground, low]);
+    }
 }
@@ -0,0 +1,75 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{resources::Selection, LocalPlayer, Owner, SelectionMode};
+
+/// How an entity relates to the `LocalPlayer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Allegiance {
+    /// Owned by the `LocalPlayer`.
+    Own,
+    /// Without an `Owner`.
+    Neutral,
+    /// Owned by another player.
+    Enemy,
+}
+
+/// Looks up the `Allegiance` of entities.
+#[derive(SystemParam)]
+pub struct Allegiances<'w, 's> {
+    owners: Query<'w, 's, &'static Owner>,
+    local_player: Res<'w, LocalPlayer>,
+}
+
+impl<'w, 's> Allegiances<'w, 's> {
+    pub fn of(&self, entity: Entity) -> Allegiance {
+        match self.owners.get(entity) {
+            Ok(owner) if *owner == Owner(self.local_player.0) => Allegiance::Own,
+            Ok(_) => Allegiance::Enemy,
+            Err(_) => Allegiance::Neutral,
+        }
+    }
+
+    /// Whether `entity` takes orders from the `LocalPlayer`, i.e. isn't an
+    /// inspected enemy.
+    pub fn is_orderable(&self, entity: Entity) -> bool {
+        self.of(entity) != Allegiance::Enemy
+    }
+
+    /// Keeps only the entities of the first allegiance in `priority` that any
+    /// of them have. Allegiances missing from `priority` are always dropped.
+    pub fn prioritize(
+        &self,
+        entities: impl IntoIterator<Item = Entity>,
+        priority: &[Allegiance],
+    ) -> Vec<Entity> {
+        let entities: Vec<(Entity, Allegiance)> = entities
+            .into_iter()
+            .map(|entity| (entity, self.of(entity)))
+            .collect();
+        priority
+            .iter()
+            .map(|&allegiance| {
+                entities
+                    .iter()
+                    .filter(|(_, other)| *other == allegiance)
+                    .map(|(entity, _)| *entity)
+                    .collect::<Vec<Entity>>()
+            })
+            .find(|tier| !tier.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// The selection that applying `entities` with `mode` would produce,
+    /// narrowed by `prioritize` so it never mixes allegiances.
+    pub fn resolve(
+        &self,
+        selection: &Selection,
+        mode: SelectionMode,
+        entities: impl IntoIterator<Item = Entity>,
+        priority: &[Allegiance],
+    ) -> Vec<Entity> {
+        let mut resolved = selection.clone();
+        resolved.apply(mode, entities);
+        self.prioritize(resolved.selected_units, priority)
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_mod_raycast::{RaycastMesh, RaycastSource};
+
+use crate::{
+    cleanup::TryInsert, spawn_ring, Cursor, HoverHighlighter, Hovered, PickReflector, Pickable,
+    PreviewHighlighter, RayReflector, SelectionHighlighter,
+};
+
+/// Gives every camera the user marked with `RaycastSource<RayReflector>` a
+/// second source for casting against `Pickable` meshes.
+#[allow(clippy::type_complexity)]
+pub fn add_pick_source(
+    mut commands: Commands,
+    cameras: Query<
+        Entity,
+        (
+            With<RaycastSource<RayReflector>>,
+            Without<RaycastSource<PickReflector>>,
+        ),
+    >,
+) {
+    for entity in &cameras {
+        commands
+            .entity(entity)
+            .insert(RaycastSource::<PickReflector>::new());
+    }
+}
+
+/// Makes the meshes of `Pickable` entities, including those spawned as part
+/// of a scene, ray cast-able.
+#[allow(clippy::type_complexity)]
+pub fn make_pickables_raycastable(
+    mut commands: Commands,
+    pickables: Query<Entity, With<Pickable>>,
+    children: Query<&Children>,
+    meshes: Query<
+        (),
+        (
+            With<Handle<Mesh>>,
+            Without<RaycastMesh<PickReflector>>,
+            Without<SelectionHighlighter>,
+            Without<HoverHighlighter>,
+            Without<PreviewHighlighter>,
+        ),
+    >,
+) {
+    for pickable in &pickables {
+        for entity in std::iter::once(pickable).chain(children.iter_descendants(pickable)) {
+            if meshes.contains(entity) {
+                commands
+                    .entity(entity)
+                    .insert(RaycastMesh::<PickReflector>::default());
+            }
+        }
+    }
+}
+
+/// Returns the front-most `Pickable` under the cursor, walking up from the hit
+/// mesh to its `Pickable` ancestor. Hits behind the `CursorReflector` ground
+/// seen from the same camera, e.g. units behind a hill, are ignored.
+#[allow(clippy::type_complexity)]
+pub fn pickable_under_cursor(
+    sources: &Query<(
+        &RaycastSource<PickReflector>,
+        Option<&RaycastSource<RayReflector>>,
+    )>,
+    parents: &Query<&Parent>,
+    pickables: &Query<(), With<Pickable>>,
+) -> Option<Entity> {
+    let (mut entity, _) = sources
+        .iter()
+        .filter_map(|(source, ground)| {
+            let (entity, hit) = source.get_nearest_intersection()?;
+            let ground_distance = ground
+                .and_then(|ground| ground.get_nearest_intersection())
+                .map_or(f32::MAX, |(_, ground_hit)| ground_hit.distance());
+            (hit.distance() <= ground_distance).then_some((entity, hit))
+        })
+        .min_by(|(_, a), (_, b)| a.distance().total_cmp(&b.distance()))?;
+
+    loop {
+        if pickables.contains(entity) {
+            return Some(entity);
+        }
+        entity = parents.get(entity).ok()?.get();
+    }
+}
+
+/// Tracks the `Pickable` under the cursor in `Cursor::hovered` and marks it
+/// `Hovered`.
+#[allow(clippy::too_many_arguments)]
+pub fn hover_system(
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    sources: Query<(
+        &RaycastSource<PickReflector>,
+        Option<&RaycastSource<RayReflector>>,
+    )>,
+    parents: Query<&Parent>,
+    pickables: Query<(), With<Pickable>>,
+    extents: Query<&Aabb>,
+    hover_highlights: Query<Entity, With<HoverHighlighter>>,
+) {
+    let hovered = pickable_under_cursor(&sources, &parents, &pickables);
+    if hovered == cursor.hovered {
+        return;
+    }
+
+    if let Some(mut previous) = cursor
+        .hovered
+        .and_then(|entity| commands.get_entity(entity))
+    {
+        previous.remove::<Hovered>();
+    }
+    for entity in &hover_highlights {
+        commands.entity(entity).despawn_recursive();
+    }
+    cursor.hovered = hovered;
+
+    let Some(entity) = hovered else {
+        return;
+    };
+    commands.add(TryInsert {
+        entity,
+        bundle: Hovered,
+    });
+
+    let aesthetics = &cursor.settings.aesthetics;
+    if let (true, Ok(aabb)) = (aesthetics.show_hover_ring, extents.get(entity)) {
+        spawn_ring(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            entity,
+            aabb,
+            cursor.settings.torus_offset,
+            aesthetics.selected_line_thickness / 2.,
+            aesthetics.hover_color,
+            AlphaMode::Blend,
+            (HoverHighlighter, Name::new("HoverHighlighter")),
+        );
+    }
+}
@@ -1,27 +1,205 @@
 use bevy::prelude::*;
 use std::collections::HashSet;
 
+use crate::{Allegiance, CursorContext};
+
 const BOUNDING_BOX_COLOR: Color = Color::rgba(0.0, 1.0, 0.0, 0.33);
 const SELECTED_AREA_BOX_COLOR: Color = Color::rgba(1.0, 1.0, 0.0, 0.33);
 const THICKNESS_OF_SELECTION_LINES: f32 = 0.05;
 const SELECTED_LINE_THICKNESS: f32 = 0.1;
+const HOVER_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.5);
+const WAYPOINT_COLOR: Color = Color::rgba(0.0, 0.6, 1.0, 0.66);
+const PREVIEW_COLOR: Color = Color::rgba(1.0, 1.0, 0.0, 0.25);
 
 #[derive(Resource, Default, Debug, Clone)]
 pub struct Cursor {
     pub entity: Option<Entity>,
     pub location: Vec3,
     pub pressed_location: Vec3,
+    pub screen_location: Vec2,
+    pub pressed_screen_location: Vec2,
+    /// Seconds since startup when select was last pressed.
+    pub pressed_time: f64,
+    /// What the current or just-released select press was classified as.
+    pub gesture: CursorGesture,
+    pub command_pressed_location: Option<Vec3>,
+    /// The front-most `Pickable` under the cursor.
+    pub hovered: Option<Entity>,
+    /// Whether the ground under the cursor lies within `CursorPlugin::bounds`.
+    pub in_bounds: bool,
+    /// Normal of the `CursorReflector` surface at `location`.
+    pub normal: Vec3,
+    pub context: CursorContext,
     pub selection: Selection,
     pub xyz1: Vec3,
     pub xyz2: Vec3,
+    pub screen_xy1: Vec2,
+    pub screen_xy2: Vec2,
+    /// Ground points of the lasso being drawn, or the last one drawn.
+    pub lasso: Vec<Vec3>,
+    /// Entities painted by the brush being dragged, or the last one dragged.
+    pub brushed: HashSet<Entity>,
     pub settings: CursorSettings,
 }
 
+/// The player whose units this cursor controls, compared against `Owner`.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalPlayer(pub u32);
+
 #[derive(Resource, Default, Debug, Clone)]
 pub struct Selection {
     pub entity: Option<Entity>,
     pub selected_units: HashSet<Entity>,
+    /// `selected_units` in the order they joined the selection, kept by
+    /// `apply`. Entities joining together are ordered by `Entity`.
+    pub order: Vec<Entity>,
     pub just_selected: bool,
+    pub just_clicked: bool,
+    pub mode: SelectionMode,
+    pub last_click: Option<(Entity, f64)>,
+    /// The selection as it was when select was pressed, restored if the
+    /// gesture is cancelled.
+    pub snapshot: HashSet<Entity>,
+    /// Entities inside the selection box while it is dragged.
+    pub preview: HashSet<Entity>,
+}
+
+impl Selection {
+    pub fn apply(
+        &mut self,
+        mode: SelectionMode,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> SelectionDelta {
+        let previous = self.selected_units.clone();
+        match mode {
+            SelectionMode::Replace => {
+                self.selected_units.clear();
+                self.selected_units.extend(entities);
+            }
+            SelectionMode::Add => self.selected_units.extend(entities),
+            SelectionMode::Toggle => {
+                for entity in entities {
+                    if !self.selected_units.remove(&entity) {
+                        self.selected_units.insert(entity);
+                    }
+                }
+            }
+            SelectionMode::Subtract => {
+                for entity in entities {
+                    self.selected_units.remove(&entity);
+                }
+            }
+        }
+        let delta = SelectionDelta::between(&previous, &self.selected_units);
+        let selected_units = &self.selected_units;
+        self.order.retain(|entity| selected_units.contains(entity));
+        self.order.extend(delta.added.iter().copied());
+        delta
+    }
+
+    pub fn clear(&mut self) -> SelectionDelta {
+        self.apply(SelectionMode::Replace, None)
+    }
+}
+
+/// Entities that entered and left a selection, sorted for stable ordering.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SelectionDelta {
+    pub added: Vec<Entity>,
+    pub removed: Vec<Entity>,
+}
+
+impl SelectionDelta {
+    pub fn between(previous: &HashSet<Entity>, current: &HashSet<Entity>) -> Self {
+        let mut added: Vec<Entity> = current.difference(previous).copied().collect();
+        let mut removed: Vec<Entity> = previous.difference(current).copied().collect();
+        added.sort();
+        removed.sort();
+        Self { added, removed }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// How a finished selection gesture is combined with the current selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    #[default]
+    Replace,
+    Add,
+    Toggle,
+    Subtract,
+}
+
+/// How a select press is classified while it is held and on release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorGesture {
+    #[default]
+    Idle,
+    /// Held without passing `CursorPlugin::drag_threshold` or
+    /// `CursorPlugin::click_threshold`.
+    Pressed,
+    /// Held still for longer than `CursorPlugin::long_press_time`.
+    LongPressed,
+    /// Moved past a drag threshold while held; a selection box is drawn.
+    Dragging,
+    /// Released without dragging. Lasts the frame of the release.
+    Clicked,
+    /// Released after dragging a selection box. Lasts the frame of the
+    /// release.
+    Dragged,
+    /// Aborted with `CursorAction::Cancel` or the command button. Lasts the
+    /// frame of the cancel; the following release is ignored.
+    Cancelled,
+}
+
+/// Which entities are kept when a selection would exceed
+/// `CursorPlugin::max_selected`. Ties are broken by `Entity`, so the same
+/// input always keeps the same entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionOverflow {
+    /// Closest on screen to the center of the box, or to the cursor for
+    /// clicks and recalled control groups.
+    #[default]
+    BoxCenter,
+    /// Closest to the camera.
+    Camera,
+    /// Highest `SelectionPriority`.
+    Priority,
+    /// Entities that joined the selection first, then the rest.
+    InsertionOrder,
+}
+
+/// The gesture drawn to select a group of entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionShape {
+    /// A rectangle between the press and the cursor.
+    #[default]
+    Box,
+    /// A freeform loop traced on the ground. Entities are tested against it
+    /// on the ground plane whatever the `SelectionSpace`.
+    Lasso,
+    /// A circle of `CursorPlugin::brush_radius` painted along the ground from
+    /// the press, catching every entity it passes over. Presses are never
+    /// clicks.
+    Brush,
+}
+
+/// Where the box selection is resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionSpace {
+    /// Entities whose translation lies in the world-space box spanned by the
+    /// ground points under the press and release.
+    #[default]
+    World,
+    /// Entities whose projected translation lies in the rectangle dragged on
+    /// screen. Matches what the player sees when the camera is rotated.
+    ScreenPosition,
+    /// Entities whose projected `Aabb` overlaps the rectangle dragged on
+    /// screen.
+    ScreenAabb,
 }
 
 #[derive(Debug, Clone, Resource, Reflect)]
@@ -38,6 +216,13 @@ pub struct Aesthetics {
     pub selected_area_box_color: Color,
     pub line_thickness: f32,
     pub selected_line_thickness: f32,
+    pub waypoint_color: Color,
+    pub hover_color: Color,
+    /// Draw a ring under the `Hovered` entity.
+    pub show_hover_ring: bool,
+    pub preview_color: Color,
+    /// Draw a ring under `Previewed` entities while a box is dragged.
+    pub show_preview_ring: bool,
 }
 
 impl Default for Aesthetics {
@@ -47,6 +232,113 @@ impl Default for Aesthetics {
             selected_area_box_color: SELECTED_AREA_BOX_COLOR,
             line_thickness: SELECTED_LINE_THICKNESS,
             selected_line_thickness: THICKNESS_OF_SELECTION_LINES,
+            waypoint_color: WAYPOINT_COLOR,
+            hover_color: HOVER_COLOR,
+            show_hover_ring: false,
+            preview_color: PREVIEW_COLOR,
+            show_preview_ring: true,
+        }
+    }
+}
+
+/// Timing for recalling `ControlGroups`. Their buttons are bound in
+/// `CursorBindings`.
+#[derive(Debug, Clone)]
+pub struct ControlGroupSettings {
+    /// Seconds between two recalls of the same group that send a
+    /// `FocusControlGroup` event.
+    pub double_tap_time: f32,
+}
+
+impl Default for ControlGroupSettings {
+    fn default() -> Self {
+        Self {
+            double_tap_time: 0.3,
+        }
+    }
+}
+
+/// Settings for `CursorCommand`s issued to the selection.
+#[derive(Debug, Clone)]
+pub struct CommandSettings {
+    /// Distance between neighbouring slots of a dragged formation.
+    pub formation_spacing: f32,
+}
+
+impl Default for CommandSettings {
+    fn default() -> Self {
+        Self {
+            formation_spacing: 1.5,
+        }
+    }
+}
+
+/// How single-finger touches are told apart.
+#[derive(Debug, Clone)]
+pub struct TouchSettings {
+    /// Pixels a finger must move before the touch becomes a box drag.
+    pub drag_threshold: f32,
+    /// Seconds a finger must rest before the touch becomes a command.
+    pub long_press_time: f32,
+}
+
+impl Default for TouchSettings {
+    fn default() -> Self {
+        Self {
+            drag_threshold: 12.,
+            long_press_time: 0.5,
+        }
+    }
+}
+
+/// Which entities the player may select, by their `Allegiance`.
+#[derive(Debug, Clone)]
+pub struct OwnershipSettings {
+    /// Allegiances that can be selected together, most preferred first. A
+    /// selection that would mix several keeps only the first one present;
+    /// allegiances not listed are never box selected.
+    pub priority: Vec<Allegiance>,
+    /// Let a click select a single entity of an allegiance missing from
+    /// `priority`, e.g. to inspect an enemy.
+    pub click_inspect: bool,
+}
+
+impl Default for OwnershipSettings {
+    fn default() -> Self {
+        Self {
+            priority: vec![Allegiance::Own, Allegiance::Neutral],
+            click_inspect: true,
+        }
+    }
+}
+
+/// A virtual cursor driven by the first connected gamepad's left stick.
+#[derive(Debug, Clone)]
+pub struct GamepadCursorSettings {
+    pub enabled: bool,
+    /// Stick deflection below which the stick counts as released.
+    pub dead_zone: f32,
+    /// Pixels per second at full deflection when the stick is first pushed.
+    pub speed: f32,
+    /// Fraction of `speed` added for every second the stick is held.
+    pub acceleration: f32,
+    pub max_speed: f32,
+    /// Pixels within which a released cursor is pulled onto a `Pickable`.
+    pub snap_radius: f32,
+    /// How quickly the cursor is pulled onto a `Pickable`, per second.
+    pub snap_strength: f32,
+}
+
+impl Default for GamepadCursorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dead_zone: 0.1,
+            speed: 400.,
+            acceleration: 1.5,
+            max_speed: 1200.,
+            snap_radius: 40.,
+            snap_strength: 10.,
         }
     }
 }
@@ -55,8 +347,36 @@ impl Default for Aesthetics {
 pub struct CursorPlugin {
     pub bounds: Bounds2D,
     pub aesthetics: Aesthetics,
+    /// Drags shorter than this, in world units, select the `Pickable` under
+    /// the cursor instead of the box contents.
+    pub click_threshold: f32,
+    /// Drags shorter than this, in screen pixels, are also clicks. A press
+    /// becomes a drag once it passes either threshold.
+    pub drag_threshold: f32,
+    /// Seconds a press must be held still to count as a long press.
+    pub long_press_time: f32,
+    /// Leave the selection untouched when a box selects nothing, instead of
+    /// replacing it with an empty one.
+    pub keep_selection_on_empty_box: bool,
+    /// Most entities that can be selected at once.
+    pub max_selected: Option<usize>,
+    pub overflow: SelectionOverflow,
+    /// Seconds between two clicks on the same entity that count as a
+    /// double-click.
+    pub double_click_time: f32,
+    pub selection_space: SelectionSpace,
+    pub selection_shape: SelectionShape,
+    /// Radius, in world units, of the `SelectionShape::Brush` circle.
+    pub brush_radius: f32,
+    pub control_groups: ControlGroupSettings,
+    pub command: CommandSettings,
     pub y_inclusion_limit: f32,
     pub torus_offset: f32,
+    /// Show `bevy_mod_raycast`'s debug cursor at the ground intersection.
+    pub debug_cursor: bool,
+    pub touch: TouchSettings,
+    pub gamepad: GamepadCursorSettings,
+    pub ownership: OwnershipSettings,
 }
 
 pub type CursorSettings = CursorPlugin;
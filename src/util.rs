@@ -1,7 +1,11 @@
-use bevy::prelude::Vec3;
+use bevy::prelude::{Camera, GlobalTransform, Vec2, Vec3};
+use bevy::render::primitives::Aabb;
 
 use crate::Bounds2D;
 
+/// Raise outlines drawn on the ground slightly or they will clip with it.
+pub const OUTLINE_HEIGHT: f32 = 0.05;
+
 pub fn keep_in_bounds(bounds: &Bounds2D, mut pos: Vec3, padding: f32) -> Vec3 {
     if pos.x < bounds.min_x + padding {
         pos.x = bounds.min_x + padding
@@ -34,3 +38,140 @@ pub fn hypotenuse(a: f32, b: f32) -> f32 {
 pub fn map_value_to_range(value: f32, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> f32 {
     (value - in_min) * (out_max - out_min) / (in_max - in_min) + out_min
 }
+
+pub fn is_point_in_rect(point: Vec2, (min, max): (Vec2, Vec2)) -> bool {
+    point.cmpge(min).all() && point.cmple(max).all()
+}
+
+pub fn is_on_screen(camera: &Camera, camera_transform: &GlobalTransform, position: Vec3) -> bool {
+    match (
+        camera.world_to_viewport(camera_transform, position),
+        camera.logical_viewport_size(),
+    ) {
+        (Some(point), Some(size)) => is_point_in_rect(point, (Vec2::ZERO, size)),
+        _ => false,
+    }
+}
+
+pub fn rects_overlap((min1, max1): (Vec2, Vec2), (min2, max2): (Vec2, Vec2)) -> bool {
+    min1.cmple(max2).all() && min2.cmple(max1).all()
+}
+
+/// Projects the corners of `aabb` to the viewport and returns the bounding
+/// rectangle of those that are in front of the camera.
+pub fn project_aabb(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    transform: &GlobalTransform,
+    aabb: &Aabb,
+) -> Option<(Vec2, Vec2)> {
+    let center = Vec3::from(aabb.center);
+    let half_extents = Vec3::from(aabb.half_extents);
+
+    let mut bounds: Option<(Vec2, Vec2)> = None;
+    for i in 0..8 {
+        let sign = Vec3::new(
+            if i & 1 == 0 { -1. } else { 1. },
+            if i & 2 == 0 { -1. } else { 1. },
+            if i & 4 == 0 { -1. } else { 1. },
+        );
+        let corner = transform.transform_point(center + half_extents * sign);
+        if let Some(point) = camera.world_to_viewport(camera_transform, corner) {
+            bounds = Some(match bounds {
+                Some((min, max)) => (min.min(point), max.max(point)),
+                None => (point, point),
+            });
+        }
+    }
+    bounds
+}
+
+/// Even-odd test of whether `point` lies inside the closed `polygon`.
+pub fn is_point_in_polygon(point: Vec2, polygon: &[Vec2]) -> bool {
+    let mut inside = false;
+    let mut previous = match polygon.last() {
+        Some(&last) => last,
+        None => return false,
+    };
+    for &current in polygon {
+        if (current.y > point.y) != (previous.y > point.y)
+            && point.x
+                < (previous.x - current.x) * (point.y - current.y) / (previous.y - current.y)
+                    + current.x
+        {
+            inside = !inside;
+        }
+        previous = current;
+    }
+    inside
+}
+
+/// The center of mass of the closed `polygon`, or the mean of its points if
+/// it encloses no area.
+pub fn polygon_centroid(polygon: &[Vec2]) -> Option<Vec2> {
+    if polygon.is_empty() {
+        return None;
+    }
+    let mut area = 0.;
+    let mut centroid = Vec2::ZERO;
+    for (index, &current) in polygon.iter().enumerate() {
+        let next = polygon[(index + 1) % polygon.len()];
+        let cross = current.perp_dot(next);
+        area += cross;
+        centroid += (current + next) * cross;
+    }
+    if area.abs() <= f32::EPSILON {
+        return Some(polygon.iter().copied().sum::<Vec2>() / polygon.len() as f32);
+    }
+    Some(centroid / (3. * area))
+}
+
+/// Whether `position` lies within `radius` of `center` on the ground plane,
+/// and within `tolerance` of it vertically.
+pub fn is_position_in_circle(position: Vec3, center: Vec3, radius: f32, tolerance: f32) -> bool {
+    Vec2::new(position.x - center.x, position.z - center.z).length() <= radius
+        && (position.y - center.y).abs() <= tolerance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_in_concave_polygon() {
+        // A U shape opening upwards.
+        let polygon = [
+            Vec2::new(0., 0.),
+            Vec2::new(3., 0.),
+            Vec2::new(3., 3.),
+            Vec2::new(2., 3.),
+            Vec2::new(2., 1.),
+            Vec2::new(1., 1.),
+            Vec2::new(1., 3.),
+            Vec2::new(0., 3.),
+        ];
+        assert!(is_point_in_polygon(Vec2::new(0.5, 2.), &polygon));
+        assert!(is_point_in_polygon(Vec2::new(2.5, 2.), &polygon));
+        assert!(is_point_in_polygon(Vec2::new(1.5, 0.5), &polygon));
+        // Inside the notch, between the arms.
+        assert!(!is_point_in_polygon(Vec2::new(1.5, 2.), &polygon));
+        assert!(!is_point_in_polygon(Vec2::new(4., 2.), &polygon));
+    }
+
+    #[test]
+    fn polygon_centroid_weighs_by_area() {
+        // Extra points along one edge don't pull the centroid towards it.
+        let square = [
+            Vec2::new(0., 0.),
+            Vec2::new(1., 0.),
+            Vec2::new(2., 0.),
+            Vec2::new(2., 2.),
+            Vec2::new(0., 2.),
+        ];
+        assert!(polygon_centroid(&square)
+            .unwrap()
+            .abs_diff_eq(Vec2::ONE, 1e-5));
+        assert_eq!(polygon_centroid(&[Vec2::ZERO, Vec2::X * 2.]), Some(Vec2::X));
+        assert_eq!(polygon_centroid(&[]), None);
+    }
+}
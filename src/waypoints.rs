@@ -0,0 +1,262 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::{
+    cleanup::TryInsert, effects::Blinker, formation::FormationCommand, util::OUTLINE_HEIGHT,
+    Cursor, CursorCommand,
+};
+
+pub const COMMAND_PING_BLINK_DURATION: f32 = 0.08;
+const WAYPOINT_MARKER_RADIUS: f32 = 0.25;
+
+/// Points an entity has been ordered through, front first. Games pop the
+/// front with `advance` when the entity arrives there.
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct WaypointQueue(pub Vec<Vec3>);
+
+impl WaypointQueue {
+    pub fn current(&self) -> Option<Vec3> {
+        self.0.first().copied()
+    }
+
+    pub fn advance(&mut self) -> Option<Vec3> {
+        if self.0.is_empty() {
+            None
+        } else {
+            Some(self.0.remove(0))
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct WaypointPath;
+
+#[derive(Resource)]
+pub struct WaypointAssets {
+    pub line_mesh: Handle<Mesh>,
+    pub marker_mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+    pub ghost_material: Handle<StandardMaterial>,
+}
+
+pub fn setup_waypoint_assets(
+    mut commands: Commands,
+    cursor: Res<Cursor>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let aesthetics = &cursor.settings.aesthetics;
+    commands.insert_resource(WaypointAssets {
+        line_mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        marker_mesh: meshes.add(Mesh::from(shape::Torus {
+            radius: WAYPOINT_MARKER_RADIUS,
+            ring_radius: aesthetics.line_thickness / 2.,
+            ..default()
+        })),
+        material: materials.add(StandardMaterial {
+            alpha_mode: AlphaMode::Blend,
+            base_color: aesthetics.waypoint_color,
+            emissive: aesthetics.waypoint_color,
+            ..default()
+        }),
+        ghost_material: materials.add(StandardMaterial {
+            alpha_mode: AlphaMode::Blend,
+            base_color: aesthetics.bounding_box_color,
+            emissive: aesthetics.bounding_box_color,
+            ..default()
+        }),
+    });
+}
+
+/// Replaces or, for queued orders, extends the `WaypointQueue` of each
+/// commanded entity, and pings the ordered points.
+pub fn waypoint_command_system(
+    mut commands: Commands,
+    cursor: Res<Cursor>,
+    waypoint_assets: Res<WaypointAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut orders: EventReader<CursorCommand>,
+    mut formations: EventReader<FormationCommand>,
+    mut queues: Query<&mut WaypointQueue>,
+) {
+    let mut waypoints: Vec<(Entity, Vec3, bool)> = Vec::new();
+    let mut pings: Vec<Vec3> = Vec::new();
+    for order in orders.iter() {
+        waypoints.extend(
+            order
+                .targets
+                .iter()
+                .map(|&target| (target, order.point, order.queued)),
+        );
+        pings.push(order.point);
+    }
+    for formation in formations.iter() {
+        waypoints.extend(
+            formation
+                .targets
+                .iter()
+                .map(|target| (target.entity, target.position, formation.queued)),
+        );
+        pings.extend(formation.targets.iter().map(|target| target.position));
+    }
+
+    for (target, point, queued) in waypoints {
+        match queues.get_mut(target) {
+            Ok(mut queue) if queued => queue.0.push(point),
+            Ok(mut queue) => queue.0 = vec![point],
+            Err(_) => commands.add(TryInsert {
+                entity: target,
+                bundle: WaypointQueue(vec![point]),
+            }),
+        }
+    }
+
+    for point in pings {
+        // The blinker fades its own material, so the ping can't share one.
+        let color = cursor.settings.aesthetics.waypoint_color;
+        commands
+            .spawn(PbrBundle {
+                mesh: waypoint_assets.marker_mesh.clone(),
+                material: materials.add(StandardMaterial {
+                    alpha_mode: AlphaMode::Blend,
+                    base_color: color,
+                    emissive: color,
+                    ..default()
+                }),
+                transform: Transform::from_translation(point + Vec3::Y * OUTLINE_HEIGHT)
+                    .with_scale(Vec3::splat(2.)),
+                ..default()
+            })
+            .insert(NotShadowReceiver)
+            .insert(NotShadowCaster)
+            .insert(Blinker::new(0.01, COMMAND_PING_BLINK_DURATION, 2))
+            .insert(Name::new("CommandPing"));
+    }
+}
+
+/// The entities drawing one unit's path. The leg from the unit to its first
+/// waypoint follows the unit; the rest only change with its queue.
+pub struct DrawnPath {
+    leg: Entity,
+    rest: Vec<Entity>,
+}
+
+/// Draws the path from each selected entity through its waypoints, rebuilding
+/// a path only when its entity is selected, deselected or has its
+/// `WaypointQueue` changed.
+#[allow(clippy::type_complexity)]
+pub fn waypoint_path_system(
+    mut commands: Commands,
+    cursor: Res<Cursor>,
+    waypoint_assets: Res<WaypointAssets>,
+    queues: Query<(&GlobalTransform, &WaypointQueue)>,
+    changed: Query<(), Changed<WaypointQueue>>,
+    mut legs: Query<&mut Transform, With<WaypointPath>>,
+    mut drawn: Local<HashMap<Entity, DrawnPath>>,
+) {
+    let thickness = cursor.settings.aesthetics.line_thickness / 2.;
+    let raise = Vec3::Y * OUTLINE_HEIGHT;
+
+    drawn.retain(|unit, path| {
+        let current = cursor.selection.selected_units.contains(unit)
+            && queues.contains(*unit)
+            && !changed.contains(*unit);
+        if !current {
+            for entity in std::iter::once(path.leg).chain(path.rest.iter().copied()) {
+                if let Some(entity_commands) = commands.get_entity(entity) {
+                    entity_commands.despawn_recursive();
+                }
+            }
+        }
+        current
+    });
+
+    for &unit in &cursor.selection.selected_units {
+        if drawn.contains_key(&unit) {
+            continue;
+        }
+        let Ok((transform, queue)) = queues.get(unit) else {
+            continue;
+        };
+        let Some(first) = queue.current() else {
+            continue;
+        };
+
+        let segment = |from: Vec3, to: Vec3| {
+            (
+                PbrBundle {
+                    mesh: waypoint_assets.line_mesh.clone(),
+                    material: waypoint_assets.material.clone(),
+                    transform: segment_transform(from + raise, to + raise, thickness),
+                    ..default()
+                },
+                NotShadowReceiver,
+                NotShadowCaster,
+                WaypointPath,
+            )
+        };
+        let marker = |at: Vec3| {
+            (
+                PbrBundle {
+                    mesh: waypoint_assets.marker_mesh.clone(),
+                    material: waypoint_assets.material.clone(),
+                    transform: Transform::from_translation(at + raise),
+                    ..default()
+                },
+                NotShadowReceiver,
+                NotShadowCaster,
+                WaypointPath,
+            )
+        };
+
+        let leg = commands
+            .spawn(segment(leg_start(transform, queue), first))
+            .id();
+        let mut rest = Vec::new();
+        for (index, &to) in queue.0.iter().enumerate() {
+            if index > 0 {
+                rest.push(commands.spawn(segment(queue.0[index - 1], to)).id());
+            }
+            rest.push(commands.spawn(marker(to)).id());
+        }
+        drawn.insert(unit, DrawnPath { leg, rest });
+    }
+
+    // Keep the first leg attached to its moving unit.
+    for (unit, path) in drawn.iter() {
+        if let (Ok((transform, queue)), Ok(mut leg)) = (queues.get(*unit), legs.get_mut(path.leg)) {
+            let Some(to) = queue.current() else {
+                continue;
+            };
+            *leg = segment_transform(leg_start(transform, queue) + raise, to + raise, thickness);
+        }
+    }
+}
+
+/// Where a unit's path starts: under the unit, level with its waypoints
+/// rather than at its center.
+fn leg_start(transform: &GlobalTransform, queue: &WaypointQueue) -> Vec3 {
+    let mut from = transform.translation();
+    from.y = queue.current().map_or(from.y, |point| point.y);
+    from
+}
+
+/// A transform stretching the unit `line_mesh` cube between two points.
+pub(crate) fn segment_transform(from: Vec3, to: Vec3, thickness: f32) -> Transform {
+    let difference = to - from;
+    if difference.length() <= f32::EPSILON {
+        return Transform::from_translation(from).with_scale(Vec3::ZERO);
+    }
+    Transform::from_translation(from + difference / 2.)
+        .looking_at(to, Vec3::Y)
+        .with_scale(Vec3::new(thickness, thickness, difference.length()))
+}
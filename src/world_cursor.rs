@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+
+use crate::{Cursor, CursorContext};
+
+/// Marks a user-spawned mesh, scene or decal that follows the cursor over
+/// the `CursorReflector` surface.
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct WorldCursor {
+    /// Contexts in which this cursor is shown. Empty shows it in all of them,
+    /// so several world cursors can be swapped by context.
+    pub contexts: Vec<CursorContext>,
+    /// Distance to float above the surface along its normal.
+    pub offset: f32,
+    /// Rotate the cursor's up axis onto the surface normal.
+    pub align_to_normal: bool,
+}
+
+impl Default for WorldCursor {
+    fn default() -> Self {
+        Self {
+            contexts: Vec::new(),
+            offset: 0.05,
+            align_to_normal: true,
+        }
+    }
+}
+
+pub fn world_cursor_system(
+    cursor: Res<Cursor>,
+    mut world_cursors: Query<(&mut Transform, &mut Visibility, &WorldCursor)>,
+) {
+    let normal = cursor.normal.try_normalize().unwrap_or(Vec3::Y);
+
+    for (mut transform, mut visibility, world_cursor) in &mut world_cursors {
+        let visible =
+            world_cursor.contexts.is_empty() || world_cursor.contexts.contains(&cursor.context);
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+
+        transform.translation = cursor.location + normal * world_cursor.offset;
+        if world_cursor.align_to_normal {
+            transform.rotation = Quat::from_rotation_arc(Vec3::Y, normal);
+        }
+    }
+}